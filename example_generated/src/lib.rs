@@ -1,6 +1,8 @@
 //! Two examples of code generated from the `spaad::entangle` attribute, showcasing the public API.
 //! API stability is not guaranteed whatsoever for this module.
 
+#![allow(dead_code)]
+
 pub mod simple {
     include!("../../spaad/examples/basic.rs");
 }
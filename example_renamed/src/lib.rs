@@ -0,0 +1,115 @@
+//! An actor using spaad under another name, as it would be behind a facade crate. The generated
+//! code names spaad by the path passed with `crate`, since `::spaad` doesn't resolve here.
+//!
+//! The wrapper methods of `pub(crate)` handlers can't be called from other crates:
+//!
+//! ```compile_fail,E0624
+//! # async fn reset(counter: spaad_example_renamed::Counter) {
+//! counter.reset().await;
+//! # }
+//! ```
+//!
+//! Nor can the actor behind the wrapper be named from other crates, only reached through it:
+//!
+//! ```compile_fail,E0603
+//! let counter = spaad_example_renamed::__CounterActor::Counter { count: 0 };
+//! ```
+
+#![allow(dead_code)]
+
+use xtra::prelude::*;
+
+#[my_spaad::entangled(crate = "my_spaad", stop_on_last_drop)]
+pub struct Counter {
+    count: u64,
+}
+
+#[my_spaad::entangled(crate = "my_spaad")]
+impl Actor for Counter {}
+
+#[my_spaad::entangled(crate = "my_spaad", api = "CounterApi")]
+impl Counter {
+    #[my_spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    #[my_spaad::handler(broadcast, batch)]
+    pub fn add(&mut self, n: u64) -> u64 {
+        self.count += n;
+        self.count
+    }
+
+    #[my_spaad::handler]
+    pub async fn count(&self) -> Result<u64, xtra::Disconnected> {
+        Ok(self.count)
+    }
+
+    #[my_spaad::handler]
+    pub(crate) fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
+pub async fn count_all(counters: &[Counter]) -> Vec<Result<u64, xtra::Disconnected>> {
+    for counter in counters {
+        counter.ready().await.unwrap();
+    }
+    Counter::add_all(counters, 1).await
+}
+
+/// spaad can also be named through a re-export, as long as the path is absolute.
+pub mod deps {
+    pub use my_spaad;
+}
+
+#[crate::deps::my_spaad::entangled(crate = "crate::deps::my_spaad")]
+pub struct Greeter {
+    greeting: String,
+    renames: deps::my_spaad::Coalesce,
+}
+
+#[crate::deps::my_spaad::entangled(crate = "crate::deps::my_spaad")]
+impl Actor for Greeter {}
+
+#[crate::deps::my_spaad::entangled(crate = "crate::deps::my_spaad", fire_and_forget)]
+impl Greeter {
+    #[crate::deps::my_spaad::spawn]
+    pub fn new(greeting: String) -> Self {
+        Greeter {
+            greeting,
+            renames: Default::default(),
+        }
+    }
+
+    #[crate::deps::my_spaad::handler(coalesce = "renames")]
+    pub fn set(&mut self, greeting: String) {
+        self.greeting = greeting;
+    }
+
+    #[crate::deps::my_spaad::handler]
+    pub fn greet(&self, name: String) -> String {
+        format!("{}, {}", self.greeting, name)
+    }
+}
+
+my_spaad::entangle! {
+    #[spaad::entangled(crate = "my_spaad")]
+    pub struct Echo;
+
+    #[spaad::entangled(crate = "my_spaad")]
+    impl Actor for Echo {}
+
+    #[spaad::entangled(crate = "my_spaad")]
+    impl Echo {
+        #[my_spaad::spawn(spawner = "tokio")]
+        pub fn new() -> Self {
+            Echo
+        }
+
+        #[my_spaad::handler]
+        pub fn echo(&self, message: String) -> String {
+            message
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Third party macros alongside spaad's, with stand-ins for them from `spaad_test_macros`.
+
+use spaad_test_macros::{trace, Injectable};
+use xtra::prelude::*;
+
+#[spaad::entangled]
+#[derive(Injectable)] // derived for the actor
+#[spaad::wrapper_attr(derive(Injectable))] // derived for the wrapper
+pub struct Greeter {
+    greeting: String,
+    trace: Vec<&'static str>,
+}
+
+#[spaad::entangled]
+impl Actor for Greeter {}
+
+#[spaad::entangled]
+#[trace] // expanded after spaad, on the actor's own impl block
+impl Greeter {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new(greeting: String) -> Self {
+        Greeter {
+            greeting,
+            trace: Vec::new(),
+        }
+    }
+
+    #[spaad::handler]
+    pub fn greet(&mut self, name: String) -> String {
+        format!("{}, {}!", self.greeting, name)
+    }
+}
+
+#[trace] // expanded before spaad, on the impl block as it was written
+#[spaad::entangled]
+impl Greeter {
+    #[spaad::handler]
+    pub fn farewell(&mut self, name: String) -> String {
+        format!("Goodbye, {}!", name)
+    }
+
+    #[spaad::handler]
+    pub fn traced(&mut self) -> Vec<&'static str> {
+        self.trace.clone()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    // The derive on the wrapper only sees its address
+    assert_eq!(Greeter::injected_fields(), ["addr"]);
+    assert_eq!(
+        __GreeterActor::Greeter::injected_fields(),
+        ["greeting", "trace"]
+    );
+
+    let greeter = Greeter::new("Hello".to_string());
+    assert_eq!(greeter.greet("world".to_string()).await, "Hello, world!");
+    assert_eq!(
+        greeter.farewell("world".to_string()).await,
+        "Goodbye, world!"
+    );
+    // Either way, the handlers themselves are traced
+    assert_eq!(greeter.traced().await, ["greet", "farewell", "traced"]);
+}
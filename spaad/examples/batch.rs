@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Index {
+    entries: HashMap<u64, u64>,
+    total: u64,
+}
+
+#[spaad::entangled]
+impl Actor for Index {}
+
+#[spaad::entangled]
+impl Index {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Index {
+            entries: HashMap::new(),
+            total: 0,
+        }
+    }
+
+    #[spaad::handler(batch)]
+    pub fn add(&mut self, n: u64) -> u64 {
+        self.total += n;
+        self.total
+    }
+
+    // Each message of a batch goes through the guard on its own
+    #[spaad::handler(batch, guard = "is_key")]
+    pub async fn insert(&mut self, key: u64, value: u64) -> Option<u64> {
+        self.entries.insert(key, value)
+    }
+
+    fn is_key(&self, key: &u64, _value: &u64) -> bool {
+        *key != 0
+    }
+
+    #[spaad::handler]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let batched = Index::new();
+    let individual = Index::new();
+
+    let totals = batched.add_batch((0..1000).collect()).await.unwrap();
+    let mut expected = Vec::new();
+    for n in 0..1000 {
+        expected.push(individual.add(n).await);
+    }
+    assert_eq!(totals, expected);
+    assert_eq!(totals[999], 499_500);
+
+    let entries: Vec<(u64, u64)> = (0..1000).map(|n| (n % 500 + 1, n)).collect();
+    let mut entries_with_empty = entries.clone();
+    entries_with_empty.push((0, 0));
+    let replaced = batched.insert_batch(entries_with_empty).await.unwrap();
+    let mut expected = Vec::new();
+    for (key, value) in entries {
+        expected.push(individual.insert(key, value).await);
+    }
+    expected.push(None);
+    assert_eq!(replaced, expected);
+    assert_eq!(replaced[500], Some(0));
+    assert_eq!(batched.len().await, 500);
+
+    assert!(batched.add_batch(Vec::new()).await.unwrap().is_empty());
+}
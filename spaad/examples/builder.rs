@@ -0,0 +1,47 @@
+use std::time::Duration;
+use xtra::prelude::*;
+
+#[spaad::entangled(builder)]
+pub struct Server {
+    port: u16,
+    #[spaad::default]
+    max_connections: usize,
+    #[spaad::default]
+    timeout: Option<Duration>,
+}
+
+#[spaad::entangled]
+impl Actor for Server {}
+
+#[spaad::entangled]
+impl Server {
+    #[spaad::handler]
+    pub fn describe(&self) -> String {
+        format!(
+            "port {}, at most {} connections, timeout {:?}",
+            self.port, self.max_connections, self.timeout
+        )
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let spawner = &mut xtra::spawn::Tokio::Global;
+
+    // Fields marked `#[spaad::default]` can be left out
+    let server = Server::builder()
+        .port(8080)
+        .max_connections(16)
+        .build_and_spawn(spawner)
+        .unwrap();
+    assert_eq!(
+        server.describe().await,
+        "port 8080, at most 16 connections, timeout None"
+    );
+
+    // The others must be set
+    let missing = Server::builder()
+        .max_connections(16)
+        .build_and_spawn(spawner);
+    assert_eq!(missing.err(), Some(spaad::MissingField("port")));
+}
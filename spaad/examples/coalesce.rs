@@ -0,0 +1,74 @@
+use futures_util::future::join_all;
+use spaad::Coalesce;
+use std::time::Duration;
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Cache {
+    refreshes: Coalesce<String>,
+    rebuilds: Coalesce,
+    refreshed: Vec<String>,
+    rebuilt: u32,
+}
+
+#[spaad::entangled]
+impl Actor for Cache {}
+
+#[spaad::entangled]
+impl Cache {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Cache {
+            refreshes: Coalesce::new(),
+            rebuilds: Coalesce::new(),
+            refreshed: Vec::new(),
+            rebuilt: 0,
+        }
+    }
+
+    // Keeps the actor busy, so that messages queue up behind it
+    #[spaad::handler]
+    pub async fn pause(&mut self) {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    #[spaad::handler(coalesce = "refreshes", coalesce_key = "source")]
+    pub fn refresh(&mut self, source: String) {
+        self.refreshed.push(source);
+    }
+
+    #[spaad::handler(coalesce = "rebuilds")]
+    pub fn rebuild(&mut self) -> Option<u32> {
+        self.rebuilt += 1;
+        Some(self.rebuilt)
+    }
+
+    #[spaad::handler]
+    pub fn refreshed(&self) -> Vec<String> {
+        self.refreshed.clone()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cache = Cache::new();
+
+    // All but the first refresh from each source are queued behind it, so they are dropped
+    let pause = cache.pause();
+    let sources = ["a", "b", "a", "c"].iter().cycle().take(1000);
+    let refreshes = join_all(sources.map(|source| cache.refresh(source.to_string())));
+    let ((), _) = futures_util::join!(pause, refreshes);
+    assert_eq!(cache.refreshed().await, ["a", "b", "c"]);
+
+    // One sent after the last refresh started still runs
+    cache.refresh("a".to_string()).await;
+    assert_eq!(cache.refreshed().await, ["a", "b", "c", "a"]);
+
+    // Dropped messages are answered with the default of the return type
+    let pause = cache.pause();
+    let rebuilds = join_all((0..100).map(|_| cache.rebuild()));
+    let ((), rebuilds) = futures_util::join!(pause, rebuilds);
+    assert_eq!(rebuilds[0], Some(1));
+    assert!(rebuilds[1..].iter().all(Option::is_none));
+    assert_eq!(cache.rebuild().await, Some(2));
+}
@@ -1,4 +1,10 @@
-#[spaad::entangled]
+use std::future::Future;
+use std::pin::Pin;
+
+#[spaad::entangled(expose_addr)]
+#[spaad::wrapper_attr(non_exhaustive)]
+// The wrapper is already `Clone`, so this is left out rather than conflicting
+#[spaad::wrapper_attr(derive(Clone))]
 #[derive(Clone)]
 pub struct X<T: 'static + Send + Clone, A>
 where
@@ -18,18 +24,19 @@ where
     async fn started(&mut self, _: &mut xtra::Context<Self>) {}
 }
 
-#[spaad::entangled]
+#[spaad::entangled(handlers)]
 impl<T: 'static + Send + Clone, A> X<T, A>
 where
     A: 'static + Send + Clone,
 {
     #[spaad::spawn(spawner = "tokio")]
     #[spaad::create(rename = "create")]
+    #[spaad::reattach]
     pub fn new<Y: Into<i32>>(t: T, a: A, y: Y) -> X<T, A> {
         X { t, a, b: y.into() }
     }
 
-    #[spaad::spawn]
+    #[spaad::spawn(message_cap = 16)] // with a bounded mailbox
     pub fn new_no_spawn<Y: Into<i32>>(t: T, a: A, y: Y) -> X<T, A> {
         X { t, a, b: y.into() }
     }
@@ -39,6 +46,7 @@ where
         self.b += 1;
         h += 1.0;
         println!("hello {}", h);
+        self.extend(vec![1u8, 2u8]); // calling generic trait method on self
         println!("b = {}", self.as_ref()); // calling trait method on self
         self.blabla().await; // await needed - we are calling the async function itself.
         ctx.notify(impl_somewhere_else::Notification); // interop with normal xtra
@@ -57,11 +65,130 @@ where
         self.not_a_handler().await;
     }
 
+    #[spaad::handler(rename = "get")]
+    pub async fn do_get(&mut self) -> i32 {
+        self.const_method()
+    }
+
+    // callers of `wrapper.old_get()` are warned, but the code generated for it isn't
+    #[deprecated(note = "use `get` instead")]
+    #[spaad::handler]
+    pub async fn old_get(&mut self) -> i32 {
+        self.const_method()
+    }
+
     #[spaad::handler]
     pub async fn handle_generically<I: Into<i32> + Send + 'static>(&mut self, i: I) {
         self.b = i.into();
     }
 
+    // the bounds can also be in a where clause, which the message and wrapper method keep as well
+    #[spaad::handler]
+    pub fn widen<I>(&self, i: I) -> i32
+    where
+        I: Into<i32> + Send + 'static,
+    {
+        i.into()
+    }
+
+    // rejected messages are answered with `None` without running the handler
+    #[spaad::handler(guard = "is_valid_b")]
+    pub async fn set_b(&mut self, b: i32) -> Option<i32> {
+        Some(std::mem::replace(&mut self.b, b))
+    }
+
+    fn is_valid_b(&self, b: &i32) -> bool {
+        *b >= 0
+    }
+
+    // the wrapper takes `impl Into<Arc<[u8]>>`, so the bytes are shared rather than copied
+    #[spaad::handler(into)]
+    pub async fn checksum(&mut self, data: std::sync::Arc<[u8]>) -> u32 {
+        data.iter().map(|&byte| byte as u32).sum()
+    }
+
+    // the wrappers take an `Arc<[i32]>`, which the handlers borrow from rather than it being cloned
+    #[spaad::handler(arc_args)]
+    pub async fn sum(&mut self, values: &[i32]) -> i32 {
+        values.iter().sum()
+    }
+
+    #[spaad::handler(arc_args)]
+    pub async fn max(&mut self, values: &[i32]) -> Option<i32> {
+        values.iter().copied().max()
+    }
+
+    // the wrappers take the tuple as an argument of its own, which the handler destructures
+    #[spaad::handler]
+    pub async fn add_pair(&mut self, (a, b): (i32, i32)) -> i32 {
+        self.b += a + b;
+        self.b
+    }
+
+    #[spaad::handler]
+    pub fn sorted(&self, mut values: Vec<i32>) -> Vec<i32> {
+        values.sort_unstable(); // arguments can be bound mutably, as in any other method
+        values
+    }
+
+    // arguments are moved into the message, so they don't need to implement `Clone`
+    #[spaad::handler]
+    pub async fn take_payload(&mut self, payload: Payload) -> usize {
+        payload.0.len()
+    }
+
+    // the continuation is sent back as it is, and runs outside of the actor
+    #[spaad::handler]
+    pub async fn scaler(
+        &mut self,
+    ) -> Box<dyn Fn(i32) -> Pin<Box<dyn Future<Output = i32> + Send>> + Send> {
+        let b = self.b;
+        Box::new(move |x| Box::pin(async move { x * b }))
+    }
+
+    #[spaad::handler]
+    pub async fn doubled(&mut self) -> i32 {
+        self.double(self.b)
+    }
+
+    // left out of the wrapper's documentation, along with `internal_b_all`, but still callable
+    #[doc(hidden)]
+    #[spaad::handler(broadcast)]
+    pub fn internal_b(&self) -> i32 {
+        self.b
+    }
+
+    // `self` is the actor, so other handlers and helpers are called on it directly, and nothing
+    // is sent to its own mailbox for it to wait on
+    #[spaad::handler]
+    pub async fn set_and_double(&mut self, b: i32) -> i32 {
+        let _ = self.set_b_checked(b);
+        self.doubled().await
+    }
+
+    #[spaad::handler]
+    pub fn set_b_checked(&mut self, b: i32) -> Result<i32, i32> {
+        if b > Self::MAX_B {
+            return Err(Self::MAX_B);
+        }
+        self.b = b;
+        Ok(b)
+    }
+
+    #[spaad::handler]
+    pub async fn parse_b(&mut self, b: String) -> Result<i32, InvalidB> {
+        let b = b.trim().parse()?; // the actor's method keeps its return type, so `?` converts
+        self.set_b_checked(b).map_err(InvalidB::TooLarge)
+    }
+
+    #[spaad::handler]
+    pub async fn accept_all(&mut self, events: Vec<u8>) -> i32 {
+        for event in events {
+            self.accept(event); // calling a trait method with its own generics and where clause
+        }
+        self.b
+    }
+
     pub async fn not_a_handler(&mut self) {
         println!("almost there!");
         self.not_async();
@@ -76,15 +203,84 @@ where
         println!("one more!!");
     }
 
+    // references can be returned as long as they are `'static`
+    #[spaad::handler]
+    pub fn label(&self) -> &'static str {
+        "x"
+    }
+
     pub fn associated_func() -> i32 {
+        Self::const_func()
+    }
+
+    pub const fn const_func() -> i32 {
         0
     }
 
+    // `Self` is the actor here too, so this can use its consts from the `inner_only` impl block
+    pub const MAX_B: i32 = Self::LIMIT - 1;
+
+    const fn const_method(&self) -> i32 {
+        self.b
+    }
+
     pub fn another_assoc_func(s: &str) -> &str {
         s
     }
 }
 
+#[spaad::entangled(
+    fire_and_forget,
+    api = "Logger",
+    handlers = "LOGGING",
+    senders = "LoggerSenders"
+)]
+impl<T: 'static + Send + Clone, A> X<T, A>
+where
+    A: 'static + Send + Clone,
+{
+    #[spaad::handler]
+    pub fn log(&mut self, msg: String) {
+        println!("log: {}", msg);
+    }
+
+    #[spaad::handler(send)]
+    pub async fn flush(&mut self) {
+        println!("flushed logs");
+    }
+
+    // Not part of `Logger`, but still has a sender, which is only visible in the crate
+    #[spaad::handler]
+    pub(crate) fn rotate(&mut self) {
+        println!("rotated logs");
+    }
+}
+
+pub mod messages {
+    pub struct Reset;
+    impl xtra::Message for Reset {
+        type Result = ();
+    }
+}
+
+// Handlers taking one of these reuse it, rather than generating a message
+#[spaad::entangled(messages(messages::Reset))]
+impl<T: 'static + Send + Clone, A> X<T, A>
+where
+    A: 'static + Send + Clone,
+    Self: Snapshot, // `Self` is the actor, even in the bounds put on the wrapper
+{
+    #[spaad::handler]
+    pub fn reset(&mut self, _reset: messages::Reset) {
+        self.b = 0;
+    }
+
+    #[spaad::handler]
+    pub fn snapshot_b(&self) -> i32 {
+        self.snapshot().b
+    }
+}
+
 pub mod impl_somewhere_else {
     pub struct Notification;
     impl xtra::Message for Notification {
@@ -104,6 +300,74 @@ pub mod impl_somewhere_else {
     }
 }
 
+// only implemented on the actor, so nothing is generated for the wrapper from it
+#[spaad::entangled(inner_only)]
+impl<T: 'static + Send + Clone, A> X<T, A>
+where
+    A: 'static + Send + Clone,
+{
+    fn double(&self, n: i32) -> i32 {
+        n * Self::factor()
+    }
+
+    fn factor() -> i32 {
+        2
+    }
+
+    const LIMIT: i32 = 100;
+}
+
+pub struct Payload(Vec<u8>);
+
+#[derive(Debug, PartialEq)]
+pub enum InvalidB {
+    NotANumber,
+    TooLarge(i32),
+}
+
+impl From<std::num::ParseIntError> for InvalidB {
+    fn from(_: std::num::ParseIntError) -> Self {
+        InvalidB::NotANumber
+    }
+}
+
+pub trait Accept {
+    fn accept<E>(&mut self, event: E) -> i32
+    where
+        E: Into<i32>,
+        Self: Sized;
+}
+
+#[spaad::entangled]
+impl<T: 'static + Send + Clone, A> Accept for X<T, A>
+where
+    A: 'static + Send + Clone,
+{
+    fn accept<E>(&mut self, event: E) -> i32
+    where
+        E: Into<i32>,
+        Self: Sized,
+    {
+        self.b += event.into();
+        self.b
+    }
+}
+
+pub trait Snapshot {
+    fn snapshot(&self) -> Self;
+}
+
+#[spaad::entangled]
+impl<T: 'static + Send + Clone, A> Snapshot for X<T, A>
+where
+    A: 'static + Send + Clone,
+    Self: Clone,
+{
+    fn snapshot(&self) -> Self {
+        self.clone()
+    }
+}
+
 #[spaad::entangled]
 impl<T: 'static + Send + Clone, A> AsRef<i32> for X<T, A>
 where
@@ -114,15 +378,69 @@ where
     }
 }
 
+#[spaad::entangled]
+impl<T: 'static + Send + Clone, A, I: Into<i32>> Extend<I> for X<T, A>
+where
+    A: 'static + Send + Clone,
+{
+    fn extend<It: IntoIterator<Item = I>>(&mut self, iter: It) {
+        for i in iter {
+            self.b += i.into();
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     #[allow(unused_mut)] // for intellij we set as mut :)
     let mut x = X::<u32, u32>::new(1, 2, 0i32);
     let (addr, fut) = X::<u32, u32>::create::<i32>(1, 2, 0i32).run();
     tokio::spawn(fut);
-    let _x2: X::<u32, u32> = addr.into();
+    let _x2: X::<u32, u32> = addr.clone().into();
+    let _x4 = X { addr }; // the address field is exposed
     let _x3 = X::<u32, u32>::new_no_spawn(1, 2, 0i32, &mut xtra::spawn::Tokio::Global);
-    let _ = x.handle_generically(1i32); // ignore result
+    drop(x.handle_generically(1i32)); // ignore result
+    x.log("fire and forget".to_string()); // no need to await
+    x.flush().await;
+    let logger: Box<dyn Logger<u32, u32>> = Box::new(x.clone());
+    let logged = logger.log("through a trait object".to_string()).await;
+    assert!(logged.is_ok());
+    let (_addr, senders): (_, LoggerSenders<u32, u32>) = x.clone().into_parts();
+    let logged = (senders.log)("through a sender".to_string()).await;
+    assert!(logged.is_ok());
+    assert!((senders.rotate)().await.is_ok());
+    println!("got b = {}", x.get().await); // renamed from `do_get`
+    x.reattach(1, 2, 3i32).await; // the same address now has a new actor behind it
+    assert_eq!(x.get().await, 3);
+    assert_eq!(x.snapshot_b().await, 3);
+    x.reset(messages::Reset).await;
+    assert_eq!(x.get().await, 0);
+    assert_eq!(x.set_b(-1).await, None);
+    assert_eq!(x.set_b(2).await, Some(0));
+    assert_eq!(x.checksum(&[1u8, 2, 3][..]).await, 6);
+    let values: std::sync::Arc<[i32]> = vec![1, 5, 3].into();
+    assert_eq!(x.sum(values.clone()).await, 9);
+    assert_eq!(x.max(values).await, Some(5));
+    assert_eq!(x.add_pair((1, 2)).await, 5);
+    assert_eq!(x.take_payload(Payload(vec![0; 16])).await, 16);
+    let scale = x.scaler().await;
+    assert_eq!(scale(3).await, 15);
+    assert_eq!(x.doubled().await, 10);
+    assert_eq!(x.label().await, "x");
+    assert_eq!(x.set_b_checked(1000).await, Err(X::<u32, u32>::MAX_B));
+    assert_eq!(x.set_b_checked(5).await, Ok(5));
+    assert_eq!(x.set_and_double(5).await, 10);
+    assert_eq!(x.internal_b().await, 5);
+    assert_eq!(x.parse_b("five".into()).await, Err(InvalidB::NotANumber));
+    let too_large = x.parse_b("1000".into()).await;
+    assert_eq!(too_large, Err(InvalidB::TooLarge(X::<u32, u32>::MAX_B)));
+    assert_eq!(x.parse_b(" 5".into()).await, Ok(5));
+    assert_eq!(x.accept_all(vec![1, 2]).await, 8);
+    assert_eq!(x.sorted(vec![3, 1, 2]).await, [1, 2, 3]);
+    assert_eq!(x.widen(3u8).await, 3);
+    assert_eq!(X::<u32, u32>::const_func(), 0);
+    assert!(X::<u32, u32>::HANDLERS.contains(&"get")); // listed under the name it was renamed to
+    assert_eq!(X::<u32, u32>::LOGGING, ["log", "flush", "rotate"]);
     x.foo(1.0).await;
     assert!(x.bar().await.is_err()); // disconnected, so we assert that it returned error
 }
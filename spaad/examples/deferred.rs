@@ -0,0 +1,54 @@
+use std::future::Future;
+use tokio::sync::oneshot;
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Fetcher {
+    requests: u32,
+}
+
+#[spaad::entangled]
+impl Actor for Fetcher {}
+
+#[spaad::entangled]
+impl Fetcher {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Fetcher { requests: 0 }
+    }
+
+    // The response is awaited by the caller once this has returned, so the actor goes on to handle
+    // other messages while it waits for the slow service
+    #[spaad::handler(defer)]
+    pub fn fetch(
+        &mut self,
+        service: oneshot::Receiver<String>,
+    ) -> impl Future<Output = String> + Send + 'static {
+        self.requests += 1;
+        async move { service.await.unwrap_or_else(|_| "no response".to_string()) }
+    }
+
+    #[spaad::handler]
+    pub fn requests(&mut self) -> u32 {
+        self.requests
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let fetcher = Fetcher::new();
+    let (respond, service) = oneshot::channel();
+
+    let fetch = fetcher.fetch(service);
+    tokio::pin!(fetch);
+    // Polling the fetch first sends it first, but it is still waiting for the service when the
+    // message sent after it has been handled
+    tokio::select! {
+        biased;
+        _ = &mut fetch => panic!("the service has not responded yet"),
+        requests = fetcher.requests() => assert_eq!(requests, 1),
+    }
+
+    respond.send("fetched".to_string()).unwrap();
+    assert_eq!(fetch.await, "fetched");
+}
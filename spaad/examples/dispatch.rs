@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use xtra::prelude::*;
+
+// Predefined, so every actor handling it can hand out a sender for it
+pub struct Command(pub String);
+
+impl Message for Command {
+    type Result = String;
+}
+
+#[spaad::entangled]
+pub struct Shell {
+    history: Vec<String>,
+}
+
+#[spaad::entangled]
+impl Actor for Shell {}
+
+#[spaad::entangled]
+impl Shell {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Shell { history: vec![] }
+    }
+
+    #[spaad::handler(msg = "Command")]
+    pub fn run(&mut self, command: Command) -> String {
+        self.history.push(command.0);
+        format!("ran command {}", self.history.len())
+    }
+}
+
+#[spaad::entangled]
+pub struct Echo;
+
+#[spaad::entangled]
+impl Actor for Echo {}
+
+#[spaad::entangled(messages(Command))]
+impl Echo {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Echo
+    }
+
+    #[spaad::handler]
+    pub fn echo(&self, command: Command) -> String {
+        command.0
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    // Plugins are dispatched to by name, without knowing which actor handles them
+    let mut plugins: HashMap<&str, spaad::Sender<Command>> = HashMap::new();
+    plugins.insert("shell", Shell::new().run_sender());
+    plugins.insert("echo", Echo::new().echo_sender());
+
+    let shell = &plugins["shell"];
+    assert_eq!(
+        shell(Command("ls".to_string())).await,
+        Ok("ran command 1".to_string())
+    );
+    assert_eq!(
+        shell(Command("pwd".to_string())).await,
+        Ok("ran command 2".to_string())
+    );
+
+    let echo = &plugins["echo"];
+    assert_eq!(
+        echo(Command("hello".to_string())).await,
+        Ok("hello".to_string())
+    );
+
+    // The wrappers themselves can be stored together as channels for the message
+    let channels: Vec<Box<dyn MessageChannel<Command>>> =
+        vec![Shell::new().into(), Echo::new().into()];
+    let mut responses = Vec::new();
+    for channel in &channels {
+        responses.push(channel.send(Command("hi".to_string())).await);
+    }
+    assert_eq!(
+        responses,
+        [Ok("ran command 1".to_string()), Ok("hi".to_string())]
+    );
+}
@@ -0,0 +1,99 @@
+use xtra::prelude::*;
+
+pub mod workers {
+    use xtra::prelude::*;
+
+    // The type parameter is bounded by `Send + 'static` everywhere the actor needs it to be
+    #[spaad::entangled]
+    pub struct Worker<T> {
+        id: u32,
+        job: T,
+    }
+
+    #[spaad::entangled]
+    impl<T> Actor for Worker<T> {}
+
+    #[spaad::entangled]
+    impl<T> Worker<T> {
+        #[spaad::spawn(spawner = "tokio")]
+        pub fn new(id: u32, job: T) -> Self {
+            Worker { id, job }
+        }
+
+        #[spaad::handler]
+        pub fn id(&self) -> u32 {
+            self.id
+        }
+    }
+
+    // The macro can't see through a type alias, so the actor it stands for is given with `actor`
+    pub type Welder = Worker<String>;
+
+    #[spaad::entangled(actor = "Worker<String>")]
+    impl Welder {
+        #[spaad::handler]
+        pub fn job(&self) -> String {
+            self.job.clone()
+        }
+    }
+
+    pub mod payroll {
+        use super::Worker;
+
+        // The actor is named by its path, so the self type can be spelled with the import
+        #[spaad::entangled(actor = "crate::workers::Worker")]
+        impl<T> Worker<T> {
+            #[spaad::handler]
+            pub fn salary(&self) -> u32 {
+                1000 + self.id * 10
+            }
+        }
+    }
+}
+
+#[spaad::entangled]
+pub struct Factory {
+    next_id: u32,
+}
+
+#[spaad::entangled]
+impl Actor for Factory {}
+
+#[spaad::entangled]
+impl Factory {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Factory { next_id: 0 }
+    }
+
+    // Handlers can return the wrappers of other actors, which are spawned by the handler
+    #[spaad::handler]
+    pub fn hire(&mut self, job: String) -> workers::Worker<String> {
+        self.hire_now(job)
+    }
+
+    #[spaad::handler]
+    pub fn hire_many(&mut self, jobs: Vec<String>) -> Vec<crate::workers::Worker<String>> {
+        jobs.into_iter().map(|job| self.hire_now(job)).collect()
+    }
+
+    fn hire_now(&mut self, job: String) -> workers::Worker<String> {
+        self.next_id += 1;
+        workers::Worker::new(self.next_id, job)
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let factory = Factory::new();
+
+    let worker = factory.hire("welding".to_string()).await;
+    assert_eq!(worker.id().await, 1);
+    assert_eq!(worker.salary().await, 1010);
+    assert_eq!(worker.job().await, "welding");
+
+    let hired = factory
+        .hire_many(vec!["painting".to_string(), "sanding".to_string()])
+        .await;
+    assert_eq!(hired[1].id().await, 3);
+}
@@ -0,0 +1,62 @@
+use xtra::prelude::*;
+
+// Generates `CounterWeak` and `CounterHandle`
+#[spaad::entangled(weak)]
+pub struct Counter {
+    count: u32,
+}
+
+#[spaad::entangled]
+impl Actor for Counter {}
+
+// Gives them the handlers of this impl block too
+#[spaad::entangled(weak)]
+impl Counter {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    #[spaad::handler]
+    pub fn increment(&mut self, by: u32) -> u32 {
+        self.count += by;
+        self.count
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    #[allow(unused_mut)]
+    let mut kept = Counter::new();
+    let dropped = Counter::new();
+
+    // Strong and weak handles can be stored together
+    let handles: Vec<CounterHandle> = vec![
+        kept.clone().into(),
+        kept.downgrade().into(),
+        dropped.downgrade().into(),
+    ];
+    let stopped = dropped.downgrade();
+    drop(dropped);
+
+    for handle in &handles {
+        match handle.increment(1).await {
+            Ok(count) => println!("count = {}", count),
+            Err(xtra::Disconnected) => println!("disconnected"),
+        }
+    }
+
+    assert_eq!(kept.increment(1).await, 3);
+
+    // A weak wrapper can be upgraded for as long as the actor is running
+    let upgraded = kept.downgrade().upgrade().await.unwrap();
+    assert_eq!(upgraded.increment(1).await, 4);
+    assert!(stopped.upgrade().await.is_none());
+
+    // A registry can downgrade through `From`, and upgrade when it needs to send something
+    let registered: CounterWeak = kept.clone().into();
+    let raw: xtra::WeakAddress<_> = kept.clone().into();
+    assert!(raw.is_connected());
+    let upgraded = registered.upgrade().await.unwrap();
+    assert_eq!(upgraded.increment(1).await, 5);
+}
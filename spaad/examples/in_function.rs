@@ -0,0 +1,32 @@
+use xtra::prelude::*;
+
+#[tokio::main]
+async fn main() {
+    // Items declared in a function can't be seen from a module inside of it, so actors which use
+    // them are declared without one
+    struct Greeting(&'static str);
+
+    spaad::entangle! {
+        #[spaad::entangled(no_module)]
+        pub struct Greeter {
+            greeting: Greeting,
+        }
+
+        impl Actor for Greeter {}
+
+        impl Greeter {
+            #[spaad::spawn(spawner = "tokio")]
+            pub fn new(greeting: Greeting) -> Self {
+                Greeter { greeting }
+            }
+
+            #[spaad::handler]
+            pub fn greet(&self, name: String) -> String {
+                format!("{}, {}!", self.greeting.0, name)
+            }
+        }
+    }
+
+    let greeter = Greeter::new(Greeting("Hello"));
+    assert_eq!(greeter.greet("world".to_string()).await, "Hello, world!");
+}
@@ -0,0 +1,48 @@
+use spaad::metrics::{self, SendWait};
+use std::sync::Mutex;
+use std::time::Duration;
+use xtra::prelude::*;
+
+static WAITS: Mutex<Vec<SendWait>> = Mutex::new(Vec::new());
+
+#[spaad::entangled]
+pub struct Worker;
+
+#[spaad::entangled]
+impl Actor for Worker {}
+
+#[spaad::entangled]
+impl Worker {
+    #[spaad::spawn(spawner = "tokio", message_cap = 1)]
+    pub fn new() -> Self {
+        Worker
+    }
+
+    #[spaad::handler]
+    pub async fn work(&mut self) {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    metrics::set_send_wait_hook(|wait| WAITS.lock().unwrap().push(wait));
+
+    // With room for only one message in the mailbox, the third has to wait for the first to be
+    // handled before it can even be sent
+    let worker = Worker::new();
+    futures_util::future::join3(worker.work(), worker.work(), worker.work()).await;
+
+    {
+        let waits = WAITS.lock().unwrap();
+        assert_eq!(waits.len(), 3);
+        assert!(waits
+            .iter()
+            .all(|wait| wait.actor == "Worker" && wait.handler == "work"));
+        assert!(waits[2].waited >= Duration::from_millis(50));
+    }
+
+    metrics::clear_send_wait_hook();
+    worker.work().await;
+    assert_eq!(WAITS.lock().unwrap().len(), 3);
+}
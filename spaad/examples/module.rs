@@ -0,0 +1,80 @@
+// Every struct and impl block in the module is entangled, as in `spaad::entangle!`
+#[spaad::entangled]
+pub mod bank {
+    use xtra::prelude::*;
+
+    pub struct Ledger {
+        entries: Vec<i64>,
+    }
+
+    pub struct Account {
+        balance: i64,
+        ledger: Ledger,
+    }
+
+    impl Actor for Ledger {}
+    impl Actor for Account {}
+
+    // Left as it is written
+    #[spaad::skip]
+    pub struct Deposit(pub i64);
+
+    impl Ledger {
+        #[spaad::spawn(spawner = "tokio")]
+        pub fn new() -> Self {
+            Ledger {
+                entries: Vec::new(),
+            }
+        }
+
+        #[spaad::handler]
+        pub fn record(&mut self, amount: i64) -> usize {
+            self.entries.push(amount);
+            self.entries.len()
+        }
+
+        // Its wrapper method is `pub(crate)` too
+        #[spaad::handler]
+        pub(crate) fn total(&self) -> i64 {
+            self.entries.iter().sum()
+        }
+    }
+
+    // Arguments are still passed with the attribute
+    #[spaad::entangled(handlers)]
+    impl Account {
+        #[spaad::spawn(spawner = "tokio")]
+        pub fn new(ledger: Ledger) -> Self {
+            Account { balance: 0, ledger }
+        }
+
+        #[spaad::handler]
+        pub async fn deposit(&mut self, deposit: Deposit) -> i64 {
+            self.ledger.record(deposit.0).await;
+            self.balance += deposit.0;
+            self.balance
+        }
+    }
+}
+
+mod audit {
+    use crate::bank::Ledger;
+
+    pub async fn balanced(ledger: &Ledger, expected: i64) -> bool {
+        ledger.total().await == expected
+    }
+}
+
+use bank::{Account, Deposit, Ledger};
+
+#[tokio::main]
+async fn main() {
+    let ledger = Ledger::new();
+    let account = Account::new(ledger.clone());
+
+    assert_eq!(account.deposit(Deposit(10)).await, 10);
+    assert_eq!(account.deposit(Deposit(5)).await, 15);
+    assert_eq!(ledger.record(0).await, 3);
+    assert!(audit::balanced(&ledger, 15).await);
+    assert_eq!(Account::HANDLERS, ["deposit"]);
+}
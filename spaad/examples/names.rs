@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use xtra::prelude::*;
+
+// The actor is `DatabaseState` in logs and debug output, while its wrapper is `Database`
+#[spaad::entangled(actor_name = "DatabaseState", display)]
+#[derive(Debug)]
+pub struct Database {
+    entries: HashMap<String, String>,
+}
+
+#[spaad::entangled]
+impl Actor for Database {}
+
+#[spaad::entangled]
+impl Database {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Database {
+            entries: HashMap::new(),
+        }
+    }
+
+    #[spaad::handler]
+    pub fn insert(&mut self, key: String, value: String) {
+        self.entries.insert(key, value);
+    }
+
+    #[spaad::handler]
+    pub fn get(&self, key: String) -> Option<String> {
+        self.entries.get(&key).cloned()
+    }
+
+    #[spaad::handler]
+    pub fn debug(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+// The `Actor` impl can also be written by hand, naming the actor by its own name
+#[spaad::entangled(actor_name = "JobActor")]
+pub struct Job {
+    runs: u32,
+}
+
+impl Actor for __JobActor::JobActor {}
+
+#[spaad::entangled]
+impl Job {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Job { runs: 0 }
+    }
+
+    #[spaad::handler]
+    pub fn run(&mut self) -> u32 {
+        self.runs += 1;
+        self.runs
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    assert!(std::any::type_name::<Database>().ends_with("::Database"));
+    assert!(std::any::type_name::<__DatabaseActor::Database>().ends_with("::DatabaseState"));
+
+    let db = Database::new();
+    db.insert("key".to_string(), "value".to_string()).await;
+    assert_eq!(db.get("key".to_string()).await.as_deref(), Some("value"));
+    assert!(db.debug().await.starts_with("DatabaseState {"));
+
+    // Displaying the wrapper uses its own name
+    assert_eq!(db.to_string(), "Database(connected)");
+    let watcher = db.clone();
+    spaad::shutdown_all(vec![db]).await;
+    while watcher.address().is_connected() {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(watcher.to_string(), "Database(disconnected)");
+
+    let job = Job::new();
+    assert_eq!(job.ready().await, Ok(()));
+    assert_eq!(job.run().await, 1);
+    assert_eq!(job.run().await, 2);
+}
@@ -0,0 +1,39 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Counter {
+    count: u32,
+}
+
+#[spaad::entangled]
+impl Actor for Counter {}
+
+#[spaad::entangled(api = "CounterApi", native_async)]
+impl Counter {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    #[spaad::handler]
+    pub fn increment(&mut self, by: u32) -> u32 {
+        self.count += by;
+        self.count
+    }
+}
+
+// With native async methods the trait isn't object safe, so it is used through generics instead
+async fn increment_twice<C: CounterApi>(counter: &C) -> Result<u32, xtra::Disconnected> {
+    counter.increment(1).await?;
+    counter.increment(2).await
+}
+
+#[tokio::main]
+async fn main() {
+    let counter = Counter::new();
+    assert_eq!(increment_twice(&counter).await, Ok(3));
+
+    // The futures are still `Send`, so they can be spawned
+    let spawned = tokio::spawn(async move { CounterApi::increment(&counter, 4).await });
+    assert_eq!(spawned.await.unwrap(), Ok(7));
+}
@@ -0,0 +1,99 @@
+use spaad::Subscribers;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use xtra::prelude::*;
+
+#[derive(Clone)]
+pub struct Headline(String);
+
+impl Message for Headline {
+    type Result = ();
+}
+
+#[spaad::entangled]
+pub struct Feed {
+    // Generates `subscribe` and `unsubscribe` on the wrapper
+    #[spaad::subscribers]
+    subscribers: Subscribers<Headline>,
+    listeners: Vec<UnboundedSender<String>>,
+}
+
+#[spaad::entangled]
+impl Actor for Feed {}
+
+#[spaad::entangled]
+impl Feed {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Feed {
+            subscribers: Subscribers::new(),
+            listeners: Vec::new(),
+        }
+    }
+
+    #[spaad::handler]
+    pub fn publish(&mut self, title: String) {
+        self.listeners
+            .retain(|listener| listener.send(title.clone()).is_ok());
+        self.subscribers.notify(Headline(title));
+    }
+
+    // Subscribers which aren't actors get the receiving end of a channel
+    #[spaad::handler]
+    pub fn listen(&mut self) -> UnboundedReceiver<String> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.listeners.push(tx);
+        rx
+    }
+}
+
+#[spaad::entangled]
+pub struct Reader {
+    read: Vec<String>,
+}
+
+#[spaad::entangled]
+impl Actor for Reader {}
+
+#[spaad::entangled]
+impl Reader {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Reader { read: Vec::new() }
+    }
+
+    #[spaad::handler(msg = "Headline")]
+    pub fn headline(&mut self, headline: Headline) {
+        self.read.push(headline.0);
+    }
+
+    #[spaad::handler]
+    pub fn read(&mut self) -> Vec<String> {
+        self.read.clone()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let feed = Feed::new();
+    let first = Reader::new();
+    let second = Reader::new();
+    let mut listener = feed.listen().await;
+
+    feed.subscribe(first.clone()).await;
+    let id = feed.subscribe(second.clone()).await;
+    feed.publish("spaad released".to_string()).await;
+
+    assert!(feed.unsubscribe(id).await);
+    assert!(!feed.unsubscribe(id).await);
+    feed.publish("xtra released".to_string()).await;
+
+    // The feed only returns once it has sent each headline, so they are handled before these
+    assert_eq!(first.read().await, ["spaad released", "xtra released"]);
+    assert_eq!(second.read().await, ["spaad released"]);
+    assert_eq!(listener.recv().await.as_deref(), Some("spaad released"));
+    assert_eq!(listener.recv().await.as_deref(), Some("xtra released"));
+
+    // The channel is closed once the feed stops
+    spaad::shutdown_all(vec![feed]).await;
+    assert_eq!(listener.recv().await, None);
+}
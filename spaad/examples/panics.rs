@@ -0,0 +1,110 @@
+use spaad::HandlerError;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Calculator {
+    last: i32,
+}
+
+#[spaad::entangled]
+impl Actor for Calculator {}
+
+// A handler which panics stops the actor, but its caller is told why
+#[spaad::entangled(catch_panics)]
+impl Calculator {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Calculator { last: 0 }
+    }
+
+    #[spaad::handler]
+    pub fn divide(&mut self, a: i32, b: i32) -> i32 {
+        self.last = a / b;
+        self.last
+    }
+}
+
+// Without `catch_panics`, sending to the stopped actor panics, at the caller's location
+#[spaad::entangled]
+impl Calculator {
+    #[spaad::handler]
+    pub fn last(&self) -> i32 {
+        self.last
+    }
+}
+
+#[spaad::entangled(fire_and_forget)]
+impl Calculator {
+    #[spaad::handler]
+    pub fn clear(&mut self) {
+        self.last = 0;
+    }
+}
+
+#[spaad::entangled]
+pub struct Parser;
+
+#[spaad::entangled]
+impl Actor for Parser {}
+
+// With `keep_alive`, the actor keeps handling messages after a panic
+#[spaad::entangled(catch_panics(keep_alive))]
+impl Parser {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Parser
+    }
+
+    #[spaad::handler]
+    pub fn parse(&self, input: String) -> u32 {
+        input.parse().expect("not a number")
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let calculator = Calculator::new();
+    assert_eq!(calculator.divide(6, 3).await, Ok(2));
+    assert_eq!(
+        calculator.divide(1, 0).await,
+        Err(HandlerError::Panicked(
+            "attempt to divide by zero".to_string()
+        ))
+    );
+    assert_eq!(
+        calculator.divide(6, 3).await,
+        Err(HandlerError::Disconnected)
+    );
+
+    // A fire-and-forget method panics right away, so the panic is located at the call
+    let located = Arc::new(Mutex::new(None));
+    let hook_located = located.clone();
+    panic::set_hook(Box::new(move |info| {
+        let location = info.location().unwrap();
+        *hook_located.lock().unwrap() = Some((location.file().to_string(), location.line()));
+    }));
+    let line = line!() + 1;
+    let cleared = panic::catch_unwind(AssertUnwindSafe(|| calculator.clear()));
+    let _ = panic::take_hook();
+    assert!(cleared.is_err());
+    assert_eq!(
+        located.lock().unwrap().take(),
+        Some((file!().to_string(), line))
+    );
+
+    // A future panics when polled, so its message names where it was created instead
+    let line = line!() + 1;
+    let last = calculator.last();
+    let panicked = tokio::spawn(last).await.unwrap_err().into_panic();
+    let message = panicked.downcast::<String>().unwrap();
+    assert!(message.contains(&format!("(message sent at {}:{}:", file!(), line)));
+
+    let parser = Parser::new();
+    assert!(matches!(
+        parser.parse("one".to_string()).await,
+        Err(HandlerError::Panicked(_))
+    ));
+    assert_eq!(parser.parse("1".to_string()).await, Ok(1));
+}
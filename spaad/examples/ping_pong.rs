@@ -0,0 +1,68 @@
+use xtra::prelude::*;
+
+// Each actor holds the other's wrapper, so they are entangled together
+spaad::entangle! {
+    pub struct Ping {
+        pong: Option<Pong>,
+        count: u32,
+    }
+
+    pub struct Pong {
+        ping: Ping,
+    }
+
+    impl Actor for Ping {}
+
+    impl Actor for Pong {}
+
+    impl Ping {
+        #[spaad::spawn(spawner = "tokio")]
+        pub fn new() -> Self {
+            Ping { pong: None, count: 0 }
+        }
+
+        #[spaad::handler]
+        pub fn set_pong(&mut self, pong: Pong) {
+            self.pong = Some(pong);
+        }
+
+        #[spaad::handler]
+        pub fn count(&mut self) -> u32 {
+            self.count += 1;
+            self.count
+        }
+
+        #[spaad::handler]
+        pub async fn serve(&mut self) -> &'static str {
+            self.pong.as_ref().unwrap().volley().await
+        }
+    }
+
+    impl Pong {
+        #[spaad::spawn(spawner = "tokio")]
+        pub fn new(ping: Ping) -> Self {
+            Pong { ping }
+        }
+
+        #[spaad::handler]
+        pub async fn hit(&mut self) -> u32 {
+            self.ping.count().await
+        }
+
+        #[spaad::handler]
+        pub fn volley(&mut self) -> &'static str {
+            "pong"
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let ping = Ping::new();
+    let pong = Pong::new(ping.clone());
+    ping.set_pong(pong.clone()).await;
+
+    assert_eq!(pong.hit().await, 1);
+    assert_eq!(pong.hit().await, 2);
+    println!("ping: {}", ping.serve().await);
+}
@@ -0,0 +1,44 @@
+use futures_util::stream::{self, StreamExt};
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Parser {
+    parsed: usize,
+}
+
+#[spaad::entangled]
+impl Actor for Parser {}
+
+#[spaad::entangled]
+impl Parser {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Parser { parsed: 0 }
+    }
+
+    #[spaad::handler(stream)]
+    pub fn parse(&mut self, line: String) -> Option<u32> {
+        self.parsed += 1;
+        line.trim().parse().ok()
+    }
+
+    #[spaad::handler]
+    pub fn parsed(&self) -> usize {
+        self.parsed
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let parser = Parser::new();
+    let lines = stream::iter(vec!["1", " 2", "three", "4 "]).map(str::to_string);
+
+    let numbers: Vec<_> = parser
+        .parse_stream(lines)
+        .map(|res| res.expect("parser disconnected"))
+        .collect()
+        .await;
+
+    assert_eq!(numbers, vec![Some(1), Some(2), None, Some(4)]);
+    assert_eq!(parser.parsed().await, 4);
+}
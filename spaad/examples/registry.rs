@@ -0,0 +1,101 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Directory {
+    members: Vec<(String, Member)>,
+}
+
+#[spaad::entangled]
+impl Actor for Directory {}
+
+#[spaad::entangled(fire_and_forget)]
+impl Directory {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Directory {
+            members: Vec::new(),
+        }
+    }
+
+    #[spaad::handler]
+    pub fn register(&mut self, name: String, member: Member) {
+        self.members.push((name, member));
+    }
+
+    // `None` for a name which isn't registered, while the actor having stopped is an error
+    #[spaad::handler]
+    pub fn find(&self, name: String) -> Result<Option<Member>, xtra::Disconnected> {
+        let found = self.members.iter().find(|(member, _)| *member == name);
+        Ok(found.map(|(_, member)| member.clone()))
+    }
+
+    #[spaad::handler]
+    pub async fn greet_all(&mut self) -> Vec<String> {
+        let mut greetings = Vec::new();
+        for (_, member) in &self.members {
+            greetings.push(member.greet().await);
+        }
+        greetings
+    }
+}
+
+#[spaad::entangled]
+pub struct Member {
+    name: String,
+}
+
+#[spaad::entangled]
+impl Actor for Member {}
+
+#[spaad::entangled]
+impl Member {
+    // The address is not an argument of the generated constructor: it is the one the actor is
+    // spawned with, so the member can be registered before it has handled any messages
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new(name: &str, directory: &Directory, addr: Address<Self>) -> Self {
+        directory.register(name.to_string(), addr.into());
+        Member {
+            name: name.to_string(),
+        }
+    }
+
+    // Only the actor's own address is left out, so the directory's is taken as usual
+    #[spaad::spawn(spawner = "tokio", rename = "introduce")]
+    pub fn introduced(
+        name: &str,
+        directory: Address<__DirectoryActor::Directory>,
+        addr: Address<Self>,
+    ) -> Self {
+        Directory::from(directory).register(name.to_string(), addr.into());
+        Member {
+            name: name.to_string(),
+        }
+    }
+
+    #[spaad::handler]
+    pub fn greet(&mut self) -> String {
+        format!("hello from {}", self.name)
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let directory = Directory::new();
+    let _first = Member::new("first", &directory);
+    let _second = Member::new("second", &directory);
+
+    assert_eq!(
+        directory.greet_all().await,
+        ["hello from first", "hello from second"]
+    );
+
+    let second = directory.find("second".to_string()).await.unwrap().unwrap();
+    assert_eq!(second.greet().await, "hello from second");
+    let _third = Member::introduce("third", directory.owned_address());
+    let third = directory.find("third".to_string()).await.unwrap().unwrap();
+    assert_eq!(third.greet().await, "hello from third");
+    assert!(directory.find("fifth".to_string()).await.unwrap().is_none());
+
+    assert_eq!(spaad::shutdown_all(vec![directory.clone()]).await, [Ok(())]);
+    assert!(directory.find("second".to_string()).await.is_err());
+}
@@ -0,0 +1,108 @@
+use spaad::Reply;
+use tokio::sync::oneshot;
+use xtra::prelude::*;
+
+// Not `Clone`, so that a response is only ever moved to the caller
+#[derive(Debug, PartialEq)]
+pub struct Entry(u64);
+
+#[spaad::entangled]
+pub struct Lookup {
+    requests: u32,
+    // Where the buffer of the last batch of entries was allocated
+    last_batch: usize,
+}
+
+#[spaad::entangled]
+impl Actor for Lookup {}
+
+#[spaad::entangled(api = "LookupApi")]
+impl Lookup {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Lookup {
+            requests: 0,
+            last_batch: 0,
+        }
+    }
+
+    // The caller waits for the reply rather than for the handler, so the actor can answer later
+    // (here from a task of its own) and go on to handle other messages in the meantime
+    #[spaad::handler]
+    pub fn lookup(&mut self, id: u64, found: oneshot::Receiver<()>, reply: Reply<(u64, String)>) {
+        self.requests += 1;
+        tokio::spawn(async move {
+            found.await.unwrap();
+            reply.send((id, format!("value {}", id)));
+        });
+    }
+
+    // Dropping the reply without responding is reported like the actor having stopped
+    #[spaad::handler]
+    pub fn forget(&mut self, reply: Reply<u64>) {
+        drop(reply);
+    }
+
+    #[spaad::handler]
+    pub fn forget_fallibly(&mut self, reply: Reply<Result<u64, xtra::Disconnected>>) {
+        drop(reply);
+    }
+
+    #[spaad::handler]
+    pub fn batch(&mut self, len: u64) -> Vec<Entry> {
+        let batch: Vec<_> = (0..len).map(Entry).collect();
+        self.last_batch = batch.as_ptr() as usize;
+        batch
+    }
+
+    #[spaad::handler]
+    pub fn batch_later(&mut self, len: u64, reply: Reply<Vec<Entry>>) {
+        let batch: Vec<_> = (0..len).map(Entry).collect();
+        self.last_batch = batch.as_ptr() as usize;
+        tokio::spawn(async move { reply.send(batch) });
+    }
+
+    #[spaad::handler]
+    pub fn last_batch(&self) -> usize {
+        self.last_batch
+    }
+
+    #[spaad::handler]
+    pub fn requests(&mut self) -> u32 {
+        self.requests
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let lookup = Lookup::new();
+
+    let (slow_found, slow_lookup) = oneshot::channel();
+    let slow = lookup.lookup(1, slow_lookup);
+    tokio::pin!(slow);
+    let (fast_found, fast_lookup) = oneshot::channel();
+    fast_found.send(()).unwrap();
+    // Polling the slow lookup first sends it first, but the one sent after it is answered sooner
+    tokio::select! {
+        biased;
+        _ = &mut slow => panic!("the slow lookup was answered first"),
+        fast = lookup.lookup(2, fast_lookup) => assert_eq!(fast, (2, "value 2".to_string())),
+    }
+
+    assert_eq!(lookup.requests().await, 2);
+    slow_found.send(()).unwrap();
+    assert_eq!(slow.await, (1, "value 1".to_string()));
+
+    // Where disconnection is reported, rather than panicking, so is a dropped reply
+    assert_eq!(LookupApi::forget(&lookup).await, Err(xtra::Disconnected));
+    assert_eq!(lookup.forget_fallibly().await, Err(xtra::Disconnected));
+
+    // Responses are moved back through the mailbox, so the caller gets the very same buffer
+    let batch = lookup.batch(10_000).await;
+    assert_eq!(batch.len(), 10_000);
+    assert_eq!(batch[9_999], Entry(9_999));
+    assert_eq!(batch.as_ptr() as usize, lookup.last_batch().await);
+
+    let batch = lookup.batch_later(10_000).await;
+    assert_eq!(batch.as_ptr() as usize, lookup.last_batch().await);
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Tally {
+    total: u64,
+}
+
+#[spaad::entangled]
+impl Actor for Tally {}
+
+#[spaad::entangled]
+impl Tally {
+    #[spaad::spawn]
+    pub fn new(total: u64) -> Self {
+        Tally { total }
+    }
+
+    #[spaad::handler]
+    pub fn add(&mut self, n: u64) -> u64 {
+        self.total += n;
+        self.total
+    }
+
+    #[spaad::handler(rename = "try_total")]
+    pub fn total(&self) -> Result<u64, xtra::Disconnected> {
+        Ok(self.total)
+    }
+
+    #[spaad::handler]
+    pub fn runtime_thread(&self) -> String {
+        std::thread::current()
+            .name()
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+fn runtime(name: &str) -> Runtime {
+    Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name(name)
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+fn main() {
+    let first = runtime("first");
+    let second = runtime("second");
+
+    // Each actor runs on the runtime it was spawned onto, whichever runtime sends to it
+    let a = Tally::new_on_handle(first.handle(), 1);
+    let b = Tally::new_on_handle(second.handle(), 10);
+    assert_eq!(second.block_on(a.add(2)), 3);
+    assert_eq!(first.block_on(b.add(5)), 15);
+    assert_eq!(second.block_on(a.runtime_thread()), "first");
+    assert_eq!(first.block_on(b.runtime_thread()), "second");
+
+    // An actor spawned onto a runtime which has shut down is never run
+    let handle = first.handle().clone();
+    first.shutdown_timeout(Duration::from_secs(1));
+    let c = Tally::new_on_handle(&handle, 100);
+    assert!(second.block_on(c.try_total()).is_err());
+    assert_eq!(second.block_on(b.add(1)), 16);
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use xtra::prelude::*;
+
+pub trait Store: Send {
+    type Snapshot: Send + 'static;
+
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&mut self, key: String, value: String);
+    fn snapshot(&self) -> Self::Snapshot;
+    fn restore(&mut self, snapshot: Self::Snapshot);
+}
+
+#[derive(Default)]
+pub struct MemoryStore(HashMap<String, String>);
+
+impl Store for MemoryStore {
+    type Snapshot = HashMap<String, String>;
+
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+
+    fn set(&mut self, key: String, value: String) {
+        self.0.insert(key, value);
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        self.0.clone()
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.0 = snapshot;
+    }
+}
+
+// Answers every key with the same value, and ignores what is set
+pub struct FixedStore(&'static str);
+
+impl Store for FixedStore {
+    type Snapshot = &'static str;
+
+    fn get(&self, _key: &str) -> Option<String> {
+        Some(self.0.to_string())
+    }
+
+    fn set(&mut self, _key: String, _value: String) {}
+
+    fn snapshot(&self) -> Self::Snapshot {
+        self.0
+    }
+
+    fn restore(&mut self, snapshot: Self::Snapshot) {
+        self.0 = snapshot;
+    }
+}
+
+// The default store is kept on everything generated for the actor
+#[spaad::entangled]
+pub struct Session<S: Store = MemoryStore> {
+    store: S,
+}
+
+#[spaad::entangled]
+impl<S: Store> Actor for Session<S> {}
+
+#[spaad::entangled]
+impl<S: Store> Session<S> {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new(store: S) -> Self {
+        Session { store }
+    }
+
+    #[spaad::handler]
+    pub fn get(&self, key: String) -> Option<String> {
+        S::get(&self.store, &key)
+    }
+
+    #[spaad::handler]
+    pub fn set(&mut self, key: String, value: String) {
+        self.store.set(key, value);
+    }
+
+    // Messages can carry the associated types of the actor's type parameters, both ways
+    #[spaad::handler]
+    pub fn snapshot(&self) -> S::Snapshot {
+        self.store.snapshot()
+    }
+
+    #[spaad::handler]
+    pub fn restore(&mut self, snapshot: <S as Store>::Snapshot) {
+        self.store.restore(snapshot);
+    }
+}
+
+// The same bounds can be written in a where clause instead
+#[spaad::entangled]
+impl<S> Session<S>
+where
+    S: Store,
+{
+    #[spaad::handler]
+    pub fn get_all<K: AsRef<str> + Send + 'static>(&self, keys: Vec<K>) -> Vec<Option<String>> {
+        keys.iter()
+            .map(|key| S::get(&self.store, key.as_ref()))
+            .collect()
+    }
+}
+
+// An impl block can be for just one instantiation of the actor, which is the only one to get its
+// handlers. Each has messages of its own, even for handlers with the same name.
+#[spaad::entangled]
+impl Session<MemoryStore> {
+    #[spaad::handler]
+    pub fn len(&self) -> usize {
+        self.store.0.len()
+    }
+
+    #[spaad::handler]
+    pub fn kind(&self) -> &'static str {
+        "memory"
+    }
+}
+
+#[spaad::entangled]
+impl Session<FixedStore> {
+    #[spaad::handler]
+    pub fn kind(&self) -> &'static str {
+        "fixed"
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let session: Session = Session::new(MemoryStore::default());
+
+    session.set("user".to_string(), "ferris".to_string()).await;
+    assert_eq!(
+        session.get("user".to_string()).await.as_deref(),
+        Some("ferris")
+    );
+    assert_eq!(
+        session.get_all(vec!["user", "missing"]).await,
+        [Some("ferris".to_string()), None]
+    );
+
+    let fixed = Session::new(FixedStore("guest"));
+    fixed.set("user".to_string(), "ferris".to_string()).await;
+    assert_eq!(
+        fixed.get("user".to_string()).await.as_deref(),
+        Some("guest")
+    );
+    assert_eq!(session.kind().await, "memory");
+    assert_eq!(fixed.kind().await, "fixed");
+    assert_eq!(session.len().await, 1);
+
+    let snapshot = session.snapshot().await;
+    session.set("user".to_string(), "crab".to_string()).await;
+    session.restore(snapshot).await;
+    assert_eq!(
+        session.get("user".to_string()).await.as_deref(),
+        Some("ferris")
+    );
+    fixed.restore("admin").await;
+    assert_eq!(fixed.snapshot().await, "admin");
+}
@@ -0,0 +1,103 @@
+use tokio::sync::oneshot;
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Subscriber {
+    id: u32,
+    events: Vec<String>,
+}
+
+#[spaad::entangled]
+#[async_trait::async_trait]
+impl Actor for Subscriber {
+    async fn stopped(&mut self) {
+        println!("subscriber {} stopped", self.id);
+    }
+}
+
+#[spaad::entangled]
+impl Subscriber {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new(id: u32) -> Self {
+        Subscriber { id, events: vec![] }
+    }
+
+    #[spaad::handler(broadcast)]
+    pub fn publish(&mut self, event: String) {
+        self.events.push(event);
+    }
+
+    #[spaad::handler(broadcast)]
+    pub fn events(&self) -> usize {
+        self.events.len()
+    }
+}
+
+// With `stop_on_last_drop`, only the wrappers keep the actor running, not addresses taken from
+// them. `must_use` warns if the wrapper is dropped straight away, which would stop the actor before
+// it does anything.
+#[spaad::entangled(stop_on_last_drop, must_use)]
+pub struct Watched {
+    on_stop: Option<oneshot::Sender<()>>,
+}
+
+#[spaad::entangled]
+#[async_trait::async_trait]
+impl Actor for Watched {
+    async fn stopped(&mut self) {
+        let _ = self.on_stop.take().unwrap().send(());
+    }
+}
+
+#[spaad::entangled]
+impl Watched {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new(on_stop: oneshot::Sender<()>) -> Self {
+        Watched {
+            on_stop: Some(on_stop),
+        }
+    }
+
+    #[spaad::handler]
+    pub fn greet(&self) -> bool {
+        self.on_stop.is_some()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut subscribers: Vec<Subscriber> = (0..3).map(Subscriber::new).collect();
+
+    // Already stopped actors are reported as disconnected
+    let first = subscribers[0].clone();
+    assert_eq!(spaad::shutdown_all(vec![first]).await, vec![Ok(())]);
+
+    // Publishing doesn't wait for the subscribers, and skips over those which have stopped
+    let sent = Subscriber::publish_do_send_all(&subscribers, "started".to_string());
+    assert_eq!(sent, vec![Err(xtra::Disconnected), Ok(()), Ok(())]);
+    let events = Subscriber::events_all(&subscribers).await;
+    assert_eq!(events, vec![Err(xtra::Disconnected), Ok(1), Ok(1)]);
+
+    let results = spaad::shutdown_all(subscribers.drain(..)).await;
+    assert_eq!(results, vec![Err(xtra::Disconnected), Ok(()), Ok(())]);
+
+    // An actor also stops once the last of its wrappers is dropped, even though a strong address
+    // to it is still around, which would keep it running without `stop_on_last_drop`
+    let (tx, stopped) = oneshot::channel();
+    let watched = Watched::new(tx);
+    let watched_too = watched.clone();
+    let _addr = watched.owned_address();
+    assert!(watched.greet().await);
+    drop(watched);
+    drop(watched_too);
+    stopped.await.unwrap();
+
+    // Cleanup can wait on the actor after asking it to stop, which it finishes just afterwards
+    let (tx, stopped) = oneshot::channel();
+    let watched = Watched::new(tx);
+    assert_eq!(
+        spaad::shutdown_all(vec![watched.clone()]).await,
+        vec![Ok(())]
+    );
+    stopped.await.unwrap();
+}
@@ -0,0 +1,55 @@
+use futures_util::{future, SinkExt};
+use xtra::prelude::*;
+use xtra::sink::MessageSink;
+
+pub struct Sample(u32);
+
+impl Message for Sample {
+    type Result = ();
+}
+
+#[spaad::entangled]
+pub struct Recorder {
+    samples: Vec<u32>,
+}
+
+#[spaad::entangled]
+impl Actor for Recorder {}
+
+#[spaad::entangled]
+impl Recorder {
+    #[spaad::spawn(spawner = "tokio", message_cap = 2)]
+    pub fn new() -> Self {
+        Recorder {
+            samples: Vec::new(),
+        }
+    }
+
+    // Only handlers of messages defined elsewhere can be sent to through the address's sink
+    #[spaad::handler(msg = "Sample")]
+    pub fn record(&mut self, sample: Sample) {
+        self.samples.push(sample.0);
+    }
+
+    #[spaad::handler]
+    pub fn samples(&self) -> Vec<u32> {
+        self.samples.clone()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let recorder = Recorder::new();
+    let mut sink: Box<dyn MessageSink<Sample>> = Box::new(recorder.owned_address().into_sink());
+
+    for i in 0..10 {
+        // Ready once the mailbox has taken the previous sample, so the rest wait for the actor
+        future::poll_fn(|cx| sink.poll_ready_unpin(cx))
+            .await
+            .unwrap();
+        sink.start_send_unpin(Sample(i)).unwrap();
+    }
+    sink.flush().await.unwrap();
+
+    assert_eq!(recorder.samples().await, (0..10).collect::<Vec<_>>());
+}
@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Loader {
+    delay: Duration,
+    loaded: Option<Instant>,
+}
+
+// Startup fails by stopping the actor, as `started` can't return an error
+#[spaad::entangled]
+#[async_trait::async_trait]
+impl Actor for Loader {
+    async fn started(&mut self, ctx: &mut Context<Self>) {
+        if self.delay > Duration::from_secs(1) {
+            ctx.stop();
+            return;
+        }
+        tokio::time::sleep(self.delay).await;
+        self.loaded = Some(Instant::now());
+    }
+}
+
+#[spaad::entangled]
+impl Loader {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new(delay: Duration) -> Self {
+        Loader {
+            delay,
+            loaded: None,
+        }
+    }
+
+    #[spaad::handler]
+    pub fn loaded(&self) -> Option<Instant> {
+        self.loaded
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let sent = Instant::now();
+    let loader = Loader::new(Duration::from_millis(100));
+    loader.ready().await.unwrap();
+    let ready = Instant::now();
+    assert!(ready.duration_since(sent) >= Duration::from_millis(100));
+    assert!(loader.loaded().await.unwrap() <= ready);
+
+    // Once started, it is ready straight away
+    loader.ready().await.unwrap();
+
+    let failed = Loader::new(Duration::from_secs(60));
+    assert_eq!(failed.ready().await, Err(xtra::Disconnected));
+}
@@ -0,0 +1,63 @@
+//! Testing an actor's handlers, and code using its wrapper with a mock, which
+//! `cargo test --example testing` runs.
+
+use xtra::prelude::*;
+
+#[spaad::entangled(mock)]
+pub struct Printer {
+    printed: Vec<String>,
+}
+
+#[spaad::entangled]
+impl Actor for Printer {}
+
+#[spaad::entangled(mock)]
+impl Printer {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Printer {
+            printed: Vec::new(),
+        }
+    }
+
+    #[spaad::handler]
+    pub async fn print(&mut self, line: String) -> usize {
+        println!("{}", line);
+        self.printed.push(line);
+        self.printed.len()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let printer = Printer::new();
+    assert_eq!(printer.print("hello".to_string()).await, 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The actor itself is tested without spawning it, by calling its handlers directly
+    #[tokio::test]
+    async fn print_directly() {
+        let mut printer = Printer::__new_actor();
+        assert_eq!(printer.print("hello".to_string()).await, 1);
+        assert_eq!(printer.print("world".to_string()).await, 2);
+        assert_eq!(printer.printed, ["hello", "world"]);
+    }
+
+    // The mock has the wrapper's handler methods, but records the calls made to it instead
+    #[tokio::test]
+    async fn print_to_mock() {
+        let printer = MockPrinter::new();
+        printer.on_print(|(line,)| line.len());
+        assert_eq!(printer.print("hello".to_string()).await, 5);
+        assert_eq!(printer.print("hello, world".to_string()).await, 12);
+
+        let calls = printer.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].handler(), "print");
+        assert_eq!(calls[0].args::<(String,)>(), Some(&("hello".to_string(),)));
+    }
+}
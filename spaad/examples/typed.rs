@@ -0,0 +1,73 @@
+use std::marker::PhantomData;
+use std::str::FromStr;
+use xtra::prelude::*;
+
+// The actor holds no `T`, and only uses it in the signatures of its handlers
+#[spaad::entangled]
+pub struct Parser<T> {
+    parsed: usize,
+    _output: PhantomData<fn() -> T>,
+}
+
+#[spaad::entangled]
+impl<T> Actor for Parser<T> {}
+
+#[spaad::entangled]
+impl<T: FromStr> Parser<T> {
+    #[spaad::spawn(spawner = "tokio")]
+    pub fn new() -> Self {
+        Parser {
+            parsed: 0,
+            _output: PhantomData,
+        }
+    }
+
+    #[spaad::handler(batch)]
+    pub fn parse(&mut self, input: String) -> Option<T> {
+        let output = input.parse().ok()?;
+        self.parsed += 1;
+        Some(output)
+    }
+
+    #[spaad::handler]
+    pub fn parse_all(&mut self, inputs: Vec<String>) -> Vec<T> {
+        inputs
+            .into_iter()
+            .filter_map(|input| input.parse().ok())
+            .collect()
+    }
+
+    #[spaad::handler]
+    pub fn parsed(&self) -> usize {
+        self.parsed
+    }
+}
+
+// Impls written by hand for the actor only need the bounds its struct was written with
+impl<T> std::fmt::Debug for __ParserActor::Parser<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Parser")
+            .field("parsed", &self.parsed)
+            .finish()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let numbers: Parser<u32> = Parser::new();
+    assert_eq!(numbers.parse("7".to_string()).await, Some(7));
+    assert_eq!(numbers.parse("seven".to_string()).await, None);
+    assert_eq!(numbers.parsed().await, 1);
+    let batch = vec!["1".to_string(), "one".to_string()];
+    assert_eq!(numbers.parse_batch(batch).await.unwrap(), [Some(1), None]);
+
+    let flags: Parser<bool> = Parser::new();
+    let inputs = vec!["true".to_string(), "no".to_string(), "false".to_string()];
+    assert_eq!(flags.parse_all(inputs).await, [true, false]);
+
+    let parser = __ParserActor::Parser::<bool> {
+        parsed: 2,
+        _output: PhantomData,
+    };
+    assert_eq!(format!("{:?}", parser), "Parser { parsed: 2 }");
+}
@@ -0,0 +1,64 @@
+use xtra::prelude::*;
+use xtra::spawn::Tokio;
+
+/// An actor written with xtra alone, as it might be in another crate
+mod other_crate {
+    use xtra::prelude::*;
+
+    #[derive(Default)]
+    pub struct Counter {
+        count: u32,
+    }
+
+    impl Actor for Counter {}
+
+    pub struct Increment;
+
+    impl Message for Increment {
+        type Result = u32;
+    }
+
+    pub struct Reset;
+
+    impl Message for Reset {
+        type Result = ();
+    }
+
+    #[async_trait::async_trait]
+    impl Handler<Increment> for Counter {
+        async fn handle(&mut self, _: Increment, _: &mut Context<Self>) -> u32 {
+            self.count += 1;
+            self.count
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Handler<Reset> for Counter {
+        async fn handle(&mut self, _: Reset, _: &mut Context<Self>) {
+            self.count = 0;
+        }
+    }
+}
+
+spaad::wrap! {
+    pub struct Counter(other_crate::Counter);
+
+    impl Counter {
+        pub fn increment(&self, msg: other_crate::Increment) -> u32;
+
+        pub fn reset(&self, msg: other_crate::Reset) -> Result<(), xtra::Disconnected>;
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let counter: Counter = other_crate::Counter::default()
+        .create(None)
+        .spawn(&mut Tokio::Global)
+        .into();
+
+    assert_eq!(counter.increment(other_crate::Increment).await, 1);
+    assert_eq!(counter.clone().increment(other_crate::Increment).await, 2);
+    assert!(counter.reset(other_crate::Reset).await.is_ok());
+    assert_eq!(counter.increment(other_crate::Increment).await, 1);
+}
@@ -3,8 +3,555 @@ extern crate spaad_internal;
 #[doc(inline)]
 pub use spaad_internal::*;
 
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+pub mod mock;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::hash::Hash;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use xtra::message_channel::MessageChannel;
+use xtra::{Address, Disconnected, Handler, Message};
+
+/// Stops each of the actors, returning once all of them have handled the request to stop. The
+/// result for each actor is in the order they were given, and is `Err(Disconnected)` if it had
+/// already stopped. Any actor with a `#[spaad::entangled]` `Actor` implementation can be stopped
+/// like this. Since it consumes the wrappers, a set of them can be drained into it:
+///
+/// ```rust,ignore
+/// let results = spaad::shutdown_all(subscribers.drain()).await;
+/// ```
+///
+/// Note that the actor finishes stopping just after it handles the request, so its `stopped`
+/// method may not have returned yet.
+pub async fn shutdown_all<A, I>(actors: I) -> Vec<Result<(), Disconnected>>
+where
+    A: Handler<export::Stop>,
+    I: IntoIterator,
+    I::Item: Into<Address<A>>,
+{
+    // The addresses are kept until the end, since an actor whose last address is dropped stops
+    // without handling its messages
+    let addresses: Vec<Address<A>> = actors.into_iter().map(Into::into).collect();
+    let stops = addresses.iter().map(|addr| addr.send(export::Stop));
+
+    futures_util::future::join_all(stops).await
+}
+
+/// A closure sending a message to an actor, which can be stored and called without knowing the
+/// actor's type. The wrapper of an actor has a `{handler}_sender` method returning one for each
+/// handler which reuses a predefined message. The future resolves to the handler's response, or
+/// `Err(Disconnected)` if the actor has stopped.
+pub type Sender<M> = Box<
+    dyn Fn(M) -> Pin<Box<dyn Future<Output = Result<<M as Message>::Result, Disconnected>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Returned by the `build_and_spawn` method of a builder generated with
+/// `#[spaad::entangled(builder)]` when a field without `#[spaad::default]` was never set. It holds
+/// the name of the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingField(pub &'static str);
+
+impl fmt::Display for MissingField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the `{}` field of the actor was not set", self.0)
+    }
+}
+
+impl std::error::Error for MissingField {}
+
+/// Returned by the wrapper methods of handlers in an impl block marked
+/// `#[spaad::entangled(catch_panics)]` when the handler did not respond.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandlerError {
+    /// The actor had stopped before it handled the message.
+    Disconnected,
+    /// The handler panicked with this message.
+    Panicked(String),
+}
+
+impl fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandlerError::Disconnected => f.write_str("Actor address disconnected"),
+            HandlerError::Panicked(msg) => write!(f, "the handler panicked: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HandlerError {}
+
+impl From<Disconnected> for HandlerError {
+    fn from(_: Disconnected) -> Self {
+        HandlerError::Disconnected
+    }
+}
+
+/// The response of a handler marked `#[spaad::handler(defer)]`, which is computed after the handler
+/// has returned, so that the actor can handle other messages in the meantime. Awaiting it waits for
+/// the response, or for the actor to have stopped without sending one, as it can when the response
+/// is sent through a [`Reply`].
+pub struct Deferred<T>(Pin<Box<dyn Future<Output = Result<T, Disconnected>> + Send>>);
+
+impl<T> Deferred<T> {
+    /// Defers the response to the future, which the actor doesn't wait for.
+    pub fn new<F: Future<Output = T> + Send + 'static>(fut: F) -> Self
+    where
+        T: 'static,
+    {
+        Deferred(Box::pin(async move { Ok(fut.await) }))
+    }
+}
+
+impl<T> Future for Deferred<T> {
+    type Output = Result<T, Disconnected>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().0.as_mut().poll(cx)
+    }
+}
+
+/// Where a handler taking it as an argument sends its response. This can be done at any time,
+/// including after the handler has returned, such as once a slow service has answered. The wrapper
+/// method resolves to the response once it has been sent.
+pub struct Reply<T>(catty::Sender<T>);
+
+impl<T> Reply<T> {
+    /// Sends the response. This does nothing if the caller is no longer waiting for it.
+    pub fn send(self, response: T) {
+        let _ = self.0.send(response);
+    }
+}
+
+/// Identifies a subscription to [`Subscribers`], so that it can be unsubscribed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// The message channels subscribed to notifications of `M` from an actor. xtra's channels can't
+/// be compared with each other, so each subscription is given an id to unsubscribe it with instead.
+///
+/// A field of the actor holding this can be marked `#[spaad::subscribers]`, which generates
+/// `subscribe` and `unsubscribe` methods on the wrapper.
+pub struct Subscribers<M: Message> {
+    next_id: u64,
+    channels: Vec<(SubscriptionId, Box<dyn MessageChannel<M>>)>,
+}
+
+impl<M: Message> Subscribers<M> {
+    /// Creates an empty set of subscribers.
+    pub fn new() -> Self {
+        Subscribers {
+            next_id: 0,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Adds a subscriber, returning the id to unsubscribe it with.
+    pub fn subscribe(&mut self, channel: Box<dyn MessageChannel<M>>) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.channels.push((id, channel));
+        id
+    }
+
+    /// Returns whether the subscription was still there to be removed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.channels.len();
+        self.channels
+            .retain(|(subscription, _)| *subscription != id);
+        self.channels.len() != len
+    }
+
+    /// Sends the message to every subscriber without waiting for them to handle it, as with
+    /// `do_send`. Subscribers whose actors have stopped are unsubscribed.
+    pub fn notify(&mut self, msg: M)
+    where
+        M: Clone,
+    {
+        self.channels
+            .retain(|(_, channel)| channel.do_send(msg.clone()).is_ok());
+    }
+
+    /// Returns how many subscribers there are.
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Returns whether there are no subscribers.
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+impl<M: Message> Default for Subscribers<M> {
+    fn default() -> Self {
+        Subscribers::new()
+    }
+}
+
+/// What a handler marked `#[spaad::handler(coalesce = "...")]` keeps to tell which of its messages
+/// are redundant. It remembers when the handler last started for each key, and a message sent
+/// before then is dropped, since that run already started after it was sent.
+///
+/// An entry is kept for every key the handler has run with, until it is forgotten.
+pub struct Coalesce<K = ()> {
+    started: HashMap<K, u64>,
+}
+
+impl<K: Eq + Hash> Coalesce<K> {
+    /// Creates a `Coalesce` which hasn't seen the handler start for any key yet.
+    pub fn new() -> Self {
+        Coalesce {
+            started: HashMap::new(),
+        }
+    }
+
+    /// Forgets when the handler last started for the key, so that none of the messages with it
+    /// which have been sent so far are dropped.
+    pub fn forget(&mut self, key: &K) {
+        self.started.remove(key);
+    }
+
+    /// Forgets when the handler last started for every key.
+    pub fn clear(&mut self) {
+        self.started.clear();
+    }
+
+    /// Returns whether the message sent at `sent` should be handled, and if so, records that the
+    /// handler has started for the key.
+    #[doc(hidden)]
+    pub fn start(&mut self, key: K, sent: u64) -> bool {
+        match self.started.get(&key) {
+            Some(started) if sent < *started => false,
+            _ => {
+                self.started.insert(key, export::sequence());
+                true
+            }
+        }
+    }
+}
+
+impl<K: Eq + Hash> Default for Coalesce<K> {
+    fn default() -> Self {
+        Coalesce::new()
+    }
+}
+
 #[doc(hidden)]
 pub mod export {
     pub use async_trait;
+    pub use futures_util;
     pub use xtra;
+
+    #[cfg(feature = "with-tokio-1")]
+    pub use tokio;
+
+    /// Asks an actor to stop. Its handler is generated along with the actor's `Actor`
+    /// implementation.
+    pub struct Stop;
+
+    impl xtra::Message for Stop {
+        type Result = ();
+    }
+
+    /// The next of the numbers ordering the sends of messages to coalescing handlers and the
+    /// starts of their handlers, across all actors.
+    pub fn sequence() -> u64 {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        NEXT.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Waits for an actor to have started, as it handles no messages until `Actor::started` has
+    /// returned. Its handler is generated along with the wrapper, for any `Actor` implementation.
+    pub struct Ready;
+
+    impl xtra::Message for Ready {
+        type Result = ();
+    }
+
+    /// Implemented by every `xtra::Actor`. The wrapper's expansion requires it of the actor first,
+    /// so that a missing `Actor` impl is reported with a message saying how to add one, ahead of
+    /// the errors from everything else which needs it.
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` does not implement `xtra::Actor`",
+        label = "this actor has no `Actor` impl",
+        note = "implement it with `#[spaad::entangled] impl Actor for ... {{}}` next to the struct"
+    )]
+    pub trait IsActor {}
+
+    impl<A: xtra::Actor> IsActor for A {}
+
+    /// Checks that an argument of a handler can be sent in its message. It is called with the span
+    /// of the argument's type, so that an error points at the argument rather than the message.
+    pub fn assert_sendable<T: Send + 'static>() {}
+
+    /// Stops an actor when dropped. The wrappers of an actor with `stop_on_last_drop` share one,
+    /// so that it is dropped along with the last of them, just before its address.
+    pub struct StopOnDrop(Box<dyn Fn() + Send + Sync>);
+
+    impl StopOnDrop {
+        pub fn new<A: xtra::Handler<Stop>>(addr: &xtra::Address<A>) -> std::sync::Arc<Self> {
+            let addr = addr.downgrade();
+            std::sync::Arc::new(StopOnDrop(Box::new(move || {
+                let _ = addr.do_send(Stop);
+            })))
+        }
+    }
+
+    impl Drop for StopOnDrop {
+        fn drop(&mut self) {
+            (self.0)()
+        }
+    }
+
+    /// Makes the wrapper of an actor which was just spawned from its address. Unlike `From`, this
+    /// is also implemented with `stop_on_last_drop`, where it is the only way for a wrapper to get
+    /// a new guard, so constructors and builders use it.
+    pub trait Spawned<A: xtra::Actor> {
+        fn spawned(addr: xtra::Address<A>) -> Self;
+    }
+
+    /// Names the wrapper of an actor as its struct was written, so that impl blocks whose self type
+    /// is spelled some other way, such as with a type alias, are caught instead of looking for an
+    /// actor which doesn't exist.
+    pub trait Entangled {
+        const NAME: &'static str;
+    }
+
+    /// Compares names in a constant, where `==` can't be used on strings.
+    pub const fn same_name(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// Asks an actor for a strong address to itself, which it only has while it is running. Its
+    /// handler is generated along with the actor's `Actor` implementation.
+    pub struct Upgrade<A>(pub std::marker::PhantomData<fn() -> A>);
+
+    impl<A: xtra::Actor> xtra::Message for Upgrade<A> {
+        type Result = Option<xtra::Address<A>>;
+    }
+
+    /// Runs a handler in an impl block marked `catch_panics`, returning the message it panicked
+    /// with, if it did.
+    pub async fn catch_panic<F: std::future::Future>(
+        fut: F,
+    ) -> Result<F::Output, crate::HandlerError> {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::task::Poll;
+
+        futures_util::pin_mut!(fut);
+        futures_util::future::poll_fn(|cx| {
+            match catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx))) {
+                Ok(poll) => poll.map(Ok),
+                Err(payload) => {
+                    let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+                        msg.to_string()
+                    } else if let Some(msg) = payload.downcast_ref::<String>() {
+                        msg.clone()
+                    } else {
+                        "Box<dyn Any>".to_string()
+                    };
+                    Poll::Ready(Err(crate::HandlerError::Panicked(msg)))
+                }
+            }
+        })
+        .await
+    }
+
+    /// Waits for the deferred response of a handler marked `defer`, if the actor handled the
+    /// message.
+    pub async fn settle<T>(
+        res: Result<crate::Deferred<T>, xtra::Disconnected>,
+    ) -> Result<T, xtra::Disconnected> {
+        res?.await
+    }
+
+    /// The deferred response of a handler whose actor had disconnected before handling the message,
+    /// so that the response reports it too.
+    pub fn disconnected<T>(_: xtra::Disconnected) -> crate::Deferred<T> {
+        crate::Deferred(Box::pin(async { Err(xtra::Disconnected) }))
+    }
+
+    /// Creates the reply given to a handler taking a `Reply`, and the deferred response it sends.
+    /// A reply dropped without a response is reported as the actor having disconnected, as it
+    /// usually is when the actor stops before sending it.
+    pub fn reply_channel<T: Send + 'static>() -> (crate::Reply<T>, crate::Deferred<T>) {
+        let (tx, rx) = catty::oneshot();
+        let response = async move { rx.await.map_err(|_| xtra::Disconnected) };
+        (crate::Reply(tx), crate::Deferred(Box::pin(response)))
+    }
+
+    /// Subscribes a channel to the `#[spaad::subscribers]` field of an actor. Its handler is
+    /// generated along with the actor's struct.
+    pub struct Subscribe<M: xtra::Message>(pub Box<dyn xtra::prelude::MessageChannel<M>>);
+
+    impl<M: xtra::Message> xtra::Message for Subscribe<M> {
+        type Result = crate::SubscriptionId;
+    }
+
+    /// Unsubscribes a channel from the `#[spaad::subscribers]` field of an actor.
+    pub struct Unsubscribe(pub crate::SubscriptionId);
+
+    impl xtra::Message for Unsubscribe {
+        type Result = bool;
+    }
+
+    /// Replaces an actor's state with a new one. Its handler is generated along with the actor's
+    /// `Actor` implementation.
+    pub struct Reattach<A>(pub A);
+
+    impl<A: xtra::Actor> xtra::Message for Reattach<A> {
+        type Result = ();
+    }
+
+    /// When a generated message was sent. This is only tracked with the `metrics` feature, and is
+    /// zero-sized otherwise.
+    #[derive(Clone, Copy)]
+    pub struct SentAt {
+        #[cfg(feature = "metrics")]
+        at: std::time::Instant,
+    }
+
+    impl SentAt {
+        #[inline]
+        pub fn now() -> Self {
+            SentAt {
+                #[cfg(feature = "metrics")]
+                at: std::time::Instant::now(),
+            }
+        }
+
+        #[inline]
+        #[allow(unused_variables)]
+        pub fn record(self, actor: &'static str, handler: &'static str) {
+            #[cfg(feature = "metrics")]
+            crate::metrics::record(actor, handler, self.at.elapsed());
+        }
+    }
+}
+
+// Keeps the `_on_handle` variants of `spawn` constructors, which take a
+// `&tokio::runtime::Handle`, only when spaad itself depends on tokio.
+
+#[cfg(feature = "with-tokio-1")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __on_tokio_handle {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "with-tokio-1"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __on_tokio_handle {
+    ($($item:tt)*) => {};
+}
+
+// The spawners which can be named through `#[spaad::spawn(spawner = "...")]`. Each expands to a
+// `compile_error!` if the feature enabling its runtime is not set, as the error from xtra would
+// otherwise point at the macro rather than the missing feature.
+
+#[cfg(feature = "with-tokio-1")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spawner_tokio {
+    () => {
+        $crate::export::xtra::spawn::Tokio::Global
+    };
+}
+
+#[cfg(not(feature = "with-tokio-1"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spawner_tokio {
+    () => {
+        compile_error!(
+            "`spawner = \"tokio\"` requires the `with-tokio-1` feature of spaad to be enabled. \
+             Otherwise, use `spaad::create` or leave out `spawner` to take a spawner as an argument"
+        )
+    };
+}
+
+#[cfg(feature = "with-async_std-1")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spawner_async_std {
+    () => {
+        $crate::export::xtra::spawn::AsyncStd
+    };
+}
+
+#[cfg(not(feature = "with-async_std-1"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spawner_async_std {
+    () => {
+        compile_error!(
+            "`spawner = \"async_std\"` requires the `with-async_std-1` feature of spaad to be \
+             enabled. Otherwise, use `spaad::create` or leave out `spawner` to take a spawner as an \
+             argument"
+        )
+    };
+}
+
+#[cfg(feature = "with-smol-1")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spawner_smol {
+    () => {
+        $crate::export::xtra::spawn::Smol::Global
+    };
+}
+
+#[cfg(not(feature = "with-smol-1"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spawner_smol {
+    () => {
+        compile_error!(
+            "`spawner = \"smol\"` requires the `with-smol-1` feature of spaad to be enabled. \
+             Otherwise, use `spaad::create` or leave out `spawner` to take a spawner as an argument"
+        )
+    };
+}
+
+#[cfg(feature = "with-wasm_bindgen-0_2")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spawner_wasm_bindgen {
+    () => {
+        $crate::export::xtra::spawn::WasmBindgen
+    };
+}
+
+#[cfg(not(feature = "with-wasm_bindgen-0_2"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spawner_wasm_bindgen {
+    () => {
+        compile_error!(
+            "`spawner = \"wasm_bindgen\"` requires the `with-wasm_bindgen-0_2` feature of spaad to \
+             be enabled. Otherwise, use `spaad::create` or leave out `spawner` to take a spawner as \
+             an argument"
+        )
+    };
 }
@@ -0,0 +1,47 @@
+//! Observing how long messages wait before they are handled. This is enabled by the `metrics`
+//! feature.
+//!
+//! The wait of a message is measured from when its wrapper method was called until its handler
+//! started. This includes the time spent waiting for a slot in the actor's mailbox if it is
+//! bounded, as well as the time spent queued behind other messages. Messages reused through
+//! `#[spaad::handler(msg = "...")]` are not measured, as spaad does not generate them.
+
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// How long a message waited before it began to be handled.
+#[derive(Clone, Debug)]
+pub struct SendWait {
+    /// The name of the actor which the message was sent to.
+    pub actor: &'static str,
+    /// The name of the handler which handled the message.
+    pub handler: &'static str,
+    /// How long it took from the message being sent to its handler starting.
+    pub waited: Duration,
+}
+
+static HOOK: RwLock<Option<fn(SendWait)>> = RwLock::new(None);
+
+/// Sets the function to be called with the wait of each handled message, replacing any previously
+/// set function. It is called from inside of the actor, before the handler runs, so it should be
+/// quick.
+pub fn set_send_wait_hook(hook: fn(SendWait)) {
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(hook);
+}
+
+/// Removes the function set by [`set_send_wait_hook`](fn.set_send_wait_hook.html), if any.
+pub fn clear_send_wait_hook() {
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+pub(crate) fn record(actor: &'static str, handler: &'static str, waited: Duration) {
+    let hook = *HOOK.read().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(hook) = hook {
+        hook(SendWait {
+            actor,
+            handler,
+            waited,
+        });
+    }
+}
@@ -0,0 +1,75 @@
+//! Support for the mocks generated with the `mock` option.
+//!
+//! For an actor `MyActor` entangled with `#[spaad::entangled(mock)]`, a `MockMyActor` is generated
+//! when compiling tests. It has the handler methods of the impl blocks also marked `mock`, but
+//! rather than sending messages, it records each call and responds with what was set for that
+//! handler with its `on_{handler}` method.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// A call which was made to a handler of a mock.
+pub struct Call {
+    handler: &'static str,
+    args: Box<dyn Any + Send>,
+}
+
+impl Call {
+    /// The name of the handler which was called.
+    pub fn handler(&self) -> &'static str {
+        self.handler
+    }
+
+    /// The arguments which the handler was called with, as a tuple (e.g `(String, u32)`, or
+    /// `(String,)` for a single argument). Returns `None` if they are not of the type `T`.
+    pub fn args<T: Any>(&self) -> Option<&T> {
+        self.args.downcast_ref()
+    }
+}
+
+type Respond<A, R> = Box<dyn FnMut(&A) -> R + Send>;
+
+/// The calls made to a mock, and the responses which were set for its handlers.
+#[derive(Default)]
+pub struct Mock {
+    calls: Mutex<Vec<Call>>,
+    responses: Mutex<HashMap<&'static str, Box<dyn Any + Send>>>,
+}
+
+impl Mock {
+    /// The calls made to the mock so far, in the order they were made.
+    pub fn calls(&self) -> MutexGuard<'_, Vec<Call>> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[doc(hidden)]
+    pub fn set_response<A: Any, R: Any>(
+        &self,
+        handler: &'static str,
+        respond: impl FnMut(&A) -> R + Send + 'static,
+    ) {
+        let respond: Respond<A, R> = Box::new(respond);
+        let mut responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        responses.insert(handler, Box::new(respond));
+    }
+
+    /// Records the call, returning the response set for the handler, if there is one.
+    #[doc(hidden)]
+    pub fn call<A: Any + Send, R: Any>(&self, handler: &'static str, args: A) -> Option<R> {
+        let response = {
+            let mut responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+            // A generic handler only responds to calls with the generics its response was set for
+            responses
+                .get_mut(handler)
+                .and_then(|respond| respond.downcast_mut::<Respond<A, R>>())
+                .map(|respond| respond(&args))
+        };
+
+        self.calls().push(Call {
+            handler,
+            args: Box::new(args),
+        });
+        response
+    }
+}
@@ -1,28 +1,40 @@
-use crate::entangle::transform::transform_method;
+use crate::entangle::transform::{
+    api_method, check_renames, disconnected_panic, handler_list_entries, mentions, sender_field,
+    transform_const, transform_method,
+};
 use proc_macro::TokenStream;
 use proc_macro_error::{abort, emit_warning};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::parse::{Parse, ParseStream};
 use syn::parse_macro_input;
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::*;
 
-mod transform;
+pub mod transform;
 
 enum EntangledItem {
     Struct(ItemStruct),
     Impl(ItemImpl),
+    Mod(ItemMod),
 }
 
 impl Parse for EntangledItem {
     fn parse(input: ParseStream) -> Result<Self> {
         let attrs = input.call(Attribute::parse_outer)?;
         let lookahead = input.lookahead1();
+        let is_mod = {
+            let fork = input.fork();
+            fork.parse::<Visibility>().is_ok() && fork.peek(Token![mod])
+        };
         let expanded = if lookahead.peek(Token![impl]) {
             let mut item: ItemImpl = input.parse()?;
             item.attrs = attrs;
             EntangledItem::Impl(item)
+        } else if is_mod {
+            let mut item: ItemMod = input.parse()?;
+            item.attrs.splice(0..0, attrs);
+            EntangledItem::Mod(item)
         } else {
             let mut item: ItemStruct = input.parse()?;
             item.attrs = attrs;
@@ -32,16 +44,604 @@ impl Parse for EntangledItem {
     }
 }
 
-pub fn entangle(input: TokenStream) -> proc_macro::TokenStream {
+/// The arguments passed to `#[spaad::entangled(...)]` itself.
+#[derive(Default)]
+pub struct EntangleArgs {
+    /// Set if `()`-returning handlers should use `do_send` rather than `send` by default. The
+    /// ident is kept around to point errors at it.
+    pub fire_and_forget: Option<Ident>,
+    /// Set if the handlers should report their panics to the caller, rather than disconnecting.
+    pub catch_panics: Option<Ident>,
+    /// Set if the actor should keep running after one of the handlers catching panics panicked.
+    pub keep_alive: Option<Ident>,
+    /// The name to give the actor struct, if it should differ from the wrapper's.
+    pub actor_name: Option<LitStr>,
+    /// Set if `Display` should be implemented for the wrapper.
+    pub display: Option<Ident>,
+    /// Set if the wrapper's `addr` field should have the struct's visibility rather than be private.
+    pub expose_addr: Option<Ident>,
+    /// Set if a builder should be generated for the actor.
+    pub builder: Option<Ident>,
+    /// Set if the wrappers should stop the actor themselves once the last of them is dropped.
+    pub stop_on_last_drop: Option<Ident>,
+    /// Set if the wrapper should be `#[must_use]`, so that a handle which is dropped straight away
+    /// is warned about.
+    pub must_use: Option<Ident>,
+    /// Set if weak wrappers should be generated for the actor, or given the handlers of an impl
+    /// block.
+    pub weak: Option<Ident>,
+    /// Set if a mock should be generated for the actor in test builds, or given the handlers of an
+    /// impl block.
+    pub mock: Option<Ident>,
+    /// Set if the actor should be declared next to the wrapper rather than in a module of its own,
+    /// which is needed when they are declared inside of a function.
+    pub no_module: Option<Ident>,
+    /// Set if an impl block should only be implemented on the actor, with nothing generated for the
+    /// wrapper.
+    pub inner_only: Option<Ident>,
+    /// The path to the actor an impl block is for, if it should be used instead of its self type.
+    pub actor: Option<LitStr>,
+    /// The name of a constant to generate on the wrapper, listing the impl block's handlers.
+    pub handlers: Option<LitStr>,
+    /// The name of an object safe trait to generate with the impl block's public handlers.
+    pub api: Option<LitStr>,
+    /// Set if the API trait's methods should be native async methods returning the wrapper's
+    /// futures, rather than boxing them with `async_trait`.
+    pub native_async: Option<Ident>,
+    /// The name of a struct to generate with a closure sending each of the impl block's public
+    /// handlers' messages, which the wrapper can be split into along with its address.
+    pub senders: Option<LitStr>,
+    /// Message types defined elsewhere, which handlers taking them should reuse.
+    pub messages: Vec<Path>,
+    /// The path to spaad to use in the generated code, if it isn't reachable as `::spaad`.
+    pub krate: Option<Path>,
+}
+
+impl EntangleArgs {
+    fn from_args(args: AttributeArgs) -> Self {
+        let mut parsed = EntangleArgs::default();
+
+        for arg in args {
+            match arg {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("fire_and_forget") => {
+                    parsed.fire_and_forget = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("catch_panics") => {
+                    parsed.catch_panics = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("catch_panics") => {
+                    parsed.catch_panics = list.path.get_ident().cloned();
+                    for option in list.nested {
+                        match option {
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("keep_alive") => {
+                                parsed.keep_alive = path.get_ident().cloned();
+                            }
+                            option => abort!(option, "Expected `keep_alive`"),
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("display") => {
+                    parsed.display = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("expose_addr") => {
+                    parsed.expose_addr = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("builder") => {
+                    parsed.builder = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("stop_on_last_drop") => {
+                    parsed.stop_on_last_drop = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("must_use") => {
+                    parsed.must_use = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("weak") => {
+                    parsed.weak = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("mock") => {
+                    parsed.mock = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("no_module") => {
+                    parsed.no_module = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("inner_only") => {
+                    parsed.inner_only = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("handlers") => {
+                    parsed.handlers = Some(LitStr::new("HANDLERS", path.span()));
+                }
+                NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("handlers") => {
+                    match meta.lit {
+                        Lit::Str(lit) => parsed.handlers = Some(lit),
+                        lit => abort!(lit, "Expected the name of the handler list to be a string"),
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("actor_name") => {
+                    match meta.lit {
+                        Lit::Str(lit) => parsed.actor_name = Some(lit),
+                        lit => abort!(lit, "Expected actor name to be a string"),
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("actor") => {
+                    match meta.lit {
+                        Lit::Str(lit) => parsed.actor = Some(lit),
+                        lit => abort!(lit, "Expected the path to the actor to be a string"),
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("api") => {
+                    match meta.lit {
+                        Lit::Str(lit) => parsed.api = Some(lit),
+                        lit => abort!(lit, "Expected API trait name to be a string"),
+                    }
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("native_async") => {
+                    parsed.native_async = path.get_ident().cloned();
+                }
+                NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("senders") => {
+                    match meta.lit {
+                        Lit::Str(lit) => parsed.senders = Some(lit),
+                        lit => abort!(
+                            lit,
+                            "Expected the name of the senders struct to be a string"
+                        ),
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("crate") => {
+                    match meta.lit {
+                        Lit::Str(lit) => match lit.parse() {
+                            Ok(path) => parsed.krate = Some(path),
+                            Err(_) => abort!(lit, "`{}` is not a valid path", lit.value()),
+                        },
+                        lit => abort!(lit, "Expected the path to spaad to be a string"),
+                    }
+                }
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("messages") => {
+                    for msg in list.nested {
+                        match msg {
+                            NestedMeta::Meta(Meta::Path(path)) => parsed.messages.push(path),
+                            msg => abort!(msg, "Expected a message type"),
+                        }
+                    }
+                }
+                // xtra's `Context` always makes its own mailbox, so there's no other to plug in
+                NestedMeta::Meta(meta) if meta.path().is_ident("mailbox") => abort!(
+                    meta,
+                    "actors cannot be given a custom mailbox, since xtra always creates its own";
+                    help = "to bound the mailbox, pass `message_cap` to the constructor's `spawn` \
+                            or `create`; to drop redundant messages, mark their handler with \
+                            `coalesce`"
+                ),
+                _ => abort!(
+                    arg,
+                    "Expected one of `fire_and_forget`, `catch_panics`, `actor_name`, `display`, \
+                     `expose_addr`, `builder`, `stop_on_last_drop`, `must_use`, `weak`, \
+                     `mock`, `no_module`, `inner_only`, `actor`, `handlers`, `api`, \
+                     `native_async`, `senders`, `messages`, `crate`"
+                ),
+            }
+        }
+
+        parsed
+    }
+
+    fn check_for_struct(&self) {
+        reject_arg(&self.actor, "actor", "impl blocks");
+        reject_arg(&self.inner_only, "inner_only", "inherent impl blocks");
+        reject_arg(
+            &self.fire_and_forget,
+            "fire_and_forget",
+            "impl blocks with handlers",
+        );
+        reject_arg(
+            &self.catch_panics,
+            "catch_panics",
+            "impl blocks with handlers",
+        );
+        reject_arg(&self.handlers, "handlers", "impl blocks with handlers");
+        reject_arg(&self.api, "api", "impl blocks with handlers");
+        reject_arg(
+            &self.native_async,
+            "native_async",
+            "impl blocks with handlers",
+        );
+        reject_arg(&self.senders, "senders", "impl blocks with handlers");
+        reject_arg(
+            &self.messages.first(),
+            "messages",
+            "impl blocks with handlers",
+        );
+    }
+
+    fn check_for_trait_impl(&self) {
+        self.check_for_impl();
+        reject_arg(&self.inner_only, "inner_only", "inherent impl blocks");
+        reject_arg(
+            &self.weak,
+            "weak",
+            "the actor struct and impl blocks with handlers",
+        );
+        reject_arg(
+            &self.mock,
+            "mock",
+            "the actor struct and impl blocks with handlers",
+        );
+        reject_arg(
+            &self.fire_and_forget,
+            "fire_and_forget",
+            "impl blocks with handlers",
+        );
+        reject_arg(
+            &self.catch_panics,
+            "catch_panics",
+            "impl blocks with handlers",
+        );
+        reject_arg(&self.handlers, "handlers", "impl blocks with handlers");
+        reject_arg(&self.api, "api", "impl blocks with handlers");
+        reject_arg(
+            &self.native_async,
+            "native_async",
+            "impl blocks with handlers",
+        );
+        reject_arg(&self.senders, "senders", "impl blocks with handlers");
+        reject_arg(
+            &self.messages.first(),
+            "messages",
+            "impl blocks with handlers",
+        );
+    }
+
+    fn check_for_impl(&self) {
+        reject_arg(&self.actor_name, "actor_name", "the actor struct");
+        reject_arg(&self.display, "display", "the actor struct");
+        reject_arg(&self.expose_addr, "expose_addr", "the actor struct");
+        reject_arg(&self.builder, "builder", "the actor struct");
+        reject_arg(
+            &self.stop_on_last_drop,
+            "stop_on_last_drop",
+            "the actor struct",
+        );
+        reject_arg(&self.must_use, "must_use", "the actor struct");
+        if let (Some(native_async), None) = (&self.native_async, &self.api) {
+            abort!(
+                native_async,
+                "`native_async` can only be used along with `api`";
+                help = "it changes the methods of the API trait, so name one with `api = \"...\"`"
+            );
+        }
+    }
+}
+
+fn reject_arg<T: quote::ToTokens>(arg: &Option<T>, name: &str, valid_on: &str) {
+    if let Some(arg) = arg {
+        abort!(arg, "`{}` can only be used on {}", name, valid_on);
+    }
+}
+
+pub fn entangle(args: TokenStream, input: TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
     let item = parse_macro_input!(input as EntangledItem);
     let expanded = match item {
-        EntangledItem::Struct(s) => entangle_struct(s),
-        EntangledItem::Impl(i) => entangle_impl(i),
+        EntangledItem::Mod(m) => {
+            if let Some(arg) = args.first() {
+                abort!(
+                    arg,
+                    "arguments cannot be passed to `#[spaad::entangled]` on a module";
+                    help = "pass them to `#[spaad::entangled(...)]` on the items of the module instead"
+                );
+            }
+            entangle_mod(m)
+        }
+        EntangledItem::Struct(s) => {
+            let args = EntangleArgs::from_args(args);
+            let s = unrename_attrs(s, args.krate.as_ref());
+            replace_crate_path(entangle_struct(&args, s), args.krate.as_ref())
+        }
+        EntangledItem::Impl(i) => {
+            let args = EntangleArgs::from_args(args);
+            let i = unrename_attrs(i, args.krate.as_ref());
+            replace_crate_path(entangle_impl(&args, i), args.krate.as_ref())
+        }
     };
 
     TokenStream::from(expanded)
 }
 
+/// Replaces the path starting each attribute with `from` by `to`, in every attribute of the tokens
+/// and of the items nested in them.
+fn replace_attr_prefix(
+    tokens: proc_macro2::TokenStream,
+    from: &[String],
+    to: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    use proc_macro2::{Delimiter, Group, TokenTree};
+
+    fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+        matches!(token, Some(TokenTree::Punct(p)) if p.as_char() == c)
+    }
+
+    let mut replaced: Vec<TokenTree> = Vec::new();
+    for token in tokens {
+        let group = match token {
+            TokenTree::Group(group) => group,
+            token => {
+                replaced.push(token);
+                continue;
+            }
+        };
+
+        // Either `#[...]` or `#![...]`, rather than a macro call such as `vec![...]`
+        let mut before = replaced.iter().rev();
+        let is_attr = group.delimiter() == Delimiter::Bracket
+            && match before.next() {
+                Some(last) if is_punct(Some(last), '!') => is_punct(before.next(), '#'),
+                last => is_punct(last, '#'),
+            };
+        let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+        let prefixed = inner.len() > from.len() + 2
+            && inner
+                .iter()
+                .zip(from)
+                .all(|(token, from)| token.to_string() == *from)
+            && inner[from.len()].to_string() == ":"
+            && inner[from.len() + 1].to_string() == ":";
+
+        let stream = if is_attr && prefixed {
+            let rest: proc_macro2::TokenStream = inner[from.len()..].iter().cloned().collect();
+            let rest = replace_attr_prefix(rest, from, to);
+            quote!(#to #rest)
+        } else {
+            replace_attr_prefix(group.stream(), from, to)
+        };
+        let mut new = Group::new(group.delimiter(), stream);
+        new.set_span(group.span());
+        replaced.push(TokenTree::Group(new));
+    }
+
+    replaced.into_iter().collect()
+}
+
+/// Lets spaad's attributes on the items inside of an entangled struct or impl block be written with
+/// the path given with `crate`, by naming them `spaad::...` as the rest of the macro expects.
+fn unrename_attrs<T: Parse + quote::ToTokens>(item: T, krate: Option<&Path>) -> T {
+    let krate = match krate {
+        Some(krate) => krate,
+        None => return item,
+    };
+    let from: Vec<_> = quote!(#krate).into_iter().map(|t| t.to_string()).collect();
+    syn::parse2(replace_attr_prefix(quote!(#item), &from, &quote!(spaad))).unwrap()
+}
+
+/// Replaces the `::spaad` starting each path in the generated code with the path given with
+/// `crate`, for when spaad is only reachable under another name, such as through a facade crate.
+/// A `::spaad` following an identifier other than a keyword, as in `crate::spaad`, is a path to
+/// something else and is kept. spaad's attributes left on the actor's methods are renamed too.
+fn replace_crate_path(
+    tokens: proc_macro2::TokenStream,
+    krate: Option<&Path>,
+) -> proc_macro2::TokenStream {
+    use proc_macro2::{Group, TokenTree};
+
+    fn starts_path(prev: Option<&TokenTree>) -> bool {
+        const KEYWORDS: &[&str] = &[
+            "as", "break", "dyn", "else", "for", "impl", "in", "let", "match", "move", "mut",
+            "ref", "return", "unsafe", "use", "where",
+        ];
+        match prev {
+            Some(TokenTree::Ident(ident)) => KEYWORDS.iter().any(|keyword| ident == keyword),
+            _ => true,
+        }
+    }
+
+    fn replace(tokens: proc_macro2::TokenStream, krate: &Path) -> proc_macro2::TokenStream {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut replaced: Vec<TokenTree> = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let is_spaad = match &tokens[i..] {
+                [TokenTree::Punct(first), TokenTree::Punct(second), TokenTree::Ident(ident), ..] => {
+                    first.as_char() == ':'
+                        && first.spacing() == proc_macro2::Spacing::Joint
+                        && second.as_char() == ':'
+                        && ident == "spaad"
+                }
+                _ => false,
+            };
+
+            if is_spaad && starts_path(replaced.last()) {
+                replaced.extend(quote!(#krate));
+                i += 3;
+                continue;
+            }
+
+            replaced.push(match &tokens[i] {
+                TokenTree::Group(group) => {
+                    let mut new = Group::new(group.delimiter(), replace(group.stream(), krate));
+                    new.set_span(group.span());
+                    TokenTree::Group(new)
+                }
+                token => token.clone(),
+            });
+            i += 1;
+        }
+
+        replaced.into_iter().collect()
+    }
+
+    match krate {
+        Some(krate) => replace_attr_prefix(
+            replace(tokens, krate),
+            &["spaad".to_string()],
+            &quote!(#krate),
+        ),
+        None => tokens,
+    }
+}
+
+/// Entangles the items of a module annotated with `#[spaad::entangled]`, just like those of
+/// `spaad::entangle!`.
+fn entangle_mod(mut module: ItemMod) -> proc_macro2::TokenStream {
+    let items = match &mut module.content {
+        Some((_, items)) => std::mem::take(items),
+        None => abort!(
+            module,
+            "`#[spaad::entangled]` can only be used on modules with their items written inline"
+        ),
+    };
+
+    // The module is emitted as it was, with its inner attributes, but with the expanded items
+    let expanded = entangle_items(items);
+    module.content.as_mut().unwrap().1 = vec![Item::Verbatim(expanded)];
+    quote!(#module)
+}
+
+/// The items of a `spaad::entangle! { ... }` invocation.
+struct EntangledItems(Vec<Item>);
+
+impl Parse for EntangledItems {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut items = Vec::new();
+        while !input.is_empty() {
+            items.push(input.parse()?);
+        }
+        Ok(EntangledItems(items))
+    }
+}
+
+/// Entangles every struct and impl block in the input as if each were annotated with
+/// `#[spaad::entangled]`, which can still be written on an item to pass it arguments. Impl blocks
+/// belong to the struct named by their self type, as usual. Other items are left as they are.
+pub fn entangle_many(input: TokenStream) -> proc_macro::TokenStream {
+    let EntangledItems(items) = parse_macro_input!(input as EntangledItems);
+    TokenStream::from(entangle_items(items))
+}
+
+/// Entangles each of the items of `spaad::entangle!` or of an entangled module, other than those
+/// marked `#[spaad::skip]`.
+fn entangle_items(mut items: Vec<Item>) -> proc_macro2::TokenStream {
+    let skipped: Vec<_> = items
+        .iter_mut()
+        .map(|item| match item {
+            Item::Struct(s) => take_skip(&mut s.attrs),
+            Item::Impl(i) => take_skip(&mut i.attrs),
+            _ => true,
+        })
+        .collect();
+
+    // `no_module` changes where the impl blocks find the actor, so it only needs to be given to
+    // the struct here
+    let mut args: Vec<_> = items
+        .iter_mut()
+        .zip(&skipped)
+        .map(|(item, skipped)| match item {
+            Item::Struct(s) if !skipped => take_entangled_args(&mut s.attrs),
+            Item::Impl(i) if !skipped => take_entangled_args(&mut i.attrs),
+            _ => EntangleArgs::default(),
+        })
+        .collect();
+    let no_module: Vec<_> = items
+        .iter()
+        .zip(&args)
+        .zip(&skipped)
+        .filter(|(_, skipped)| !**skipped)
+        .filter_map(|((item, args), _)| match item {
+            Item::Struct(s) => args.no_module.as_ref().map(|_| s.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    for (item, args) in items.iter().zip(&mut args) {
+        if let Item::Impl(i) = item {
+            let mut self_ty = i.self_ty.clone();
+            retarget_self_ty(args, &mut self_ty);
+            let name = get_name_from_ty(&self_ty);
+            if args.no_module.is_none() && name.is_some_and(|name| no_module.contains(name)) {
+                args.no_module = Some(format_ident!("no_module"));
+            }
+        }
+    }
+
+    let items = items.into_iter().zip(args).zip(skipped);
+    let expanded = items.map(|((item, args), skipped)| match item {
+        Item::Struct(s) if !skipped => {
+            let s = unrename_attrs(s, args.krate.as_ref());
+            replace_crate_path(entangle_struct(&args, s), args.krate.as_ref())
+        }
+        Item::Impl(i) if !skipped => {
+            let i = unrename_attrs(i, args.krate.as_ref());
+            replace_crate_path(entangle_impl(&args, i), args.krate.as_ref())
+        }
+        item => quote!(#item),
+    });
+
+    quote!(#(#expanded)*)
+}
+
+/// Removes a `#[spaad::skip]` from an item, returning whether it was there.
+fn take_skip(attrs: &mut Vec<Attribute>) -> bool {
+    let is_skip = |attr: &Attribute| {
+        attr.path.segments.len() == 2
+            && attr.path.segments[0].ident == "spaad"
+            && attr.path.segments[1].ident == "skip"
+    };
+
+    match attrs.iter().position(is_skip) {
+        Some(position) => {
+            attrs.remove(position);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Replaces the self type of an impl block with the path given by `actor = "..."`, so that it is
+/// found the same way as if the path had been written as the self type. The generic arguments of
+/// the self type are kept, unless the path has its own.
+fn retarget_self_ty(args: &EntangleArgs, self_ty: &mut Type) {
+    let lit = match &args.actor {
+        Some(lit) => lit,
+        None => return,
+    };
+    let mut path: Path = match lit.parse() {
+        Ok(path) => path,
+        Err(_) => abort!(
+            lit,
+            "Expected the actor to be a path, such as `crate::actors::MyActor`"
+        ),
+    };
+
+    if let Type::Path(written) = &*self_ty {
+        let written = written.path.segments.last().unwrap();
+        let last = path.segments.last_mut().unwrap();
+        if last.arguments.is_empty() {
+            last.arguments = written.arguments.clone();
+        }
+    }
+
+    *self_ty = Type::Path(TypePath { qself: None, path });
+}
+
+/// Removes a `#[spaad::entangled(...)]` from an item inside of `spaad::entangle!`, returning the
+/// arguments passed to it.
+fn take_entangled_args(attrs: &mut Vec<Attribute>) -> EntangleArgs {
+    let is_entangled = |attr: &Attribute| {
+        attr.path.segments.len() == 2
+            && attr.path.segments[0].ident == "spaad"
+            && attr.path.segments[1].ident == "entangled"
+    };
+
+    let position = match attrs.iter().position(is_entangled) {
+        Some(position) => position,
+        None => return EntangleArgs::default(),
+    };
+
+    match attrs.remove(position).parse_meta() {
+        Ok(Meta::Path(_)) => EntangleArgs::default(),
+        Ok(Meta::List(list)) => EntangleArgs::from_args(list.nested.into_iter().collect()),
+        Ok(meta) => abort!(meta, "expected `#[spaad::entangled(...)]`"),
+        Err(error) => abort!(error.span(), "Failed parsing as Meta: {}", error),
+    }
+}
+
 fn set_visibility_min_pub_super(vis: &mut Visibility) {
     let mut segments = Punctuated::new();
     segments.push(PathSegment::from(format_ident!("super")));
@@ -70,76 +670,894 @@ fn set_visibility_min_pub_super(vis: &mut Visibility) {
     }
 }
 
-fn entangle_struct(struct_def: ItemStruct) -> proc_macro2::TokenStream {
+/// The visibility of the actor, which is the wrapper's, but kept inside of the crate so that other
+/// crates can only use the actor through its wrapper.
+fn actor_visibility(vis: &Visibility) -> Visibility {
+    match vis {
+        Visibility::Public(_) => parse_quote!(pub(crate)),
+        vis => vis.clone(),
+    }
+}
+
+/// The widest of the visibilities, or `pub(crate)` where two are restricted to different paths.
+/// This is private if there are none.
+fn widest_visibility<'a>(visibilities: impl IntoIterator<Item = &'a Visibility>) -> Visibility {
+    let mut widest = Visibility::Inherited;
+    for vis in visibilities {
+        widest = match (widest, vis) {
+            (_, Visibility::Public(_)) => return vis.clone(),
+            (Visibility::Inherited, vis) => vis.clone(),
+            (widest, Visibility::Inherited) => widest,
+            (widest, vis) if widest == *vis => widest,
+            _ => parse_quote!(pub(crate)),
+        };
+    }
+
+    widest
+}
+
+fn entangle_struct(args: &EntangleArgs, struct_def: ItemStruct) -> proc_macro2::TokenStream {
+    args.check_for_struct();
+
+    // Actors must be `'static`, so borrowed data with a lifetime parameter can never be held
+    if let Some(lifetime) = struct_def.generics.lifetimes().next() {
+        abort!(
+            lifetime,
+            "actors cannot have lifetime parameters, since xtra actors must be `'static`";
+            help = "hold owned data instead (e.g `String` rather than `&'a str`), or share it \
+                    with an `Arc`"
+        );
+    }
+
     let ItemStruct {
         attrs,
         vis,
         ident,
-        generics,
+        mut generics,
         mut fields,
         semi_token,
         ..
     } = struct_def;
+    let self_ty = {
+        let (_, ty_generics, _) = generics.split_for_impl();
+        parse_quote!(#ident#ty_generics)
+    };
+    // The actor struct keeps the bounds it was written with, so that impls written by hand for
+    // it do not need to repeat the ones only the wrapper and the generated impls rely on
+    let actor_generics = generics.clone();
+    let actor_where_clause = &actor_generics.where_clause;
+    add_send_bounds(&mut generics, &self_ty);
     let actor_mod = format_ident!("__{}Actor", ident);
+    let actor_vis = actor_visibility(&vis);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (wrapper_attrs, attrs) = split_wrapper_attrs(attrs);
+    let name = ident.to_string();
 
-    for field in fields.iter_mut() {
-        set_visibility_min_pub_super(&mut field.vis);
+    let mut defaults = Vec::new();
+    let mut subscriber_fields = Vec::new();
+    for (i, field) in fields.iter_mut().enumerate() {
+        if args.no_module.is_none() {
+            set_visibility_min_pub_super(&mut field.vis);
+        }
+        defaults.push(take_default_attr(args, &mut field.attrs));
+        if let Some(attr) = take_subscribers_attr(&mut field.attrs) {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            subscriber_fields.push((member, field.ty.clone(), attr));
+        }
     }
 
+    // The impl blocks refer to the actor by the wrapper's name, or by the name of the module it
+    // would be in with `no_module`, so a renamed actor is aliased to that
+    let alias_ident = if args.no_module.is_some() {
+        &actor_mod
+    } else {
+        &ident
+    };
+    let (actor_ident, actor_alias) = match &args.actor_name {
+        Some(lit) => {
+            let actor_ident: Ident = match lit.parse() {
+                Ok(ident) => ident,
+                Err(_) => abort!(lit, "Expected actor name to be a valid identifier"),
+            };
+
+            let mut alias_generics = generics.clone();
+            alias_generics.where_clause = None;
+            for param in alias_generics.params.iter_mut() {
+                match param {
+                    GenericParam::Type(ty) => {
+                        ty.colon_token = None;
+                        ty.bounds.clear();
+                    }
+                    GenericParam::Lifetime(lifetime) => {
+                        lifetime.colon_token = None;
+                        lifetime.bounds.clear();
+                    }
+                    GenericParam::Const(_) => {}
+                }
+            }
+
+            let alias_vis = if args.no_module.is_some() {
+                quote!(#actor_vis)
+            } else {
+                quote!(pub)
+            };
+            let alias = quote! {
+                #[doc(hidden)]
+                #alias_vis type #alias_ident#alias_generics = #actor_ident#ty_generics;
+            };
+
+            (actor_ident, Some(alias))
+        }
+        None => (alias_ident.clone(), None),
+    };
+
+    let (actor, actor_struct) = if args.no_module.is_some() {
+        (quote!(#actor_mod#ty_generics), quote!(#actor_ident))
+    } else {
+        (
+            quote!(#actor_mod::#ident#ty_generics),
+            quote!(#actor_mod::#actor_ident),
+        )
+    };
+
+    let display = args.display.as_ref().map(|_| {
+        quote! {
+            #[automatically_derived]
+            impl#impl_generics ::std::fmt::Display for #ident#ty_generics #where_clause {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    let state = if self.addr.is_connected() {
+                        "connected"
+                    } else {
+                        "disconnected"
+                    };
+                    write!(f, "{}({})", #name, state)
+                }
+            }
+        }
+    });
+
+    let addr_vis = args.expose_addr.as_ref().map(|_| &vis);
+
+    // xtra stops an actor once its last address is dropped, but that address only tells the actor
+    // it was the last one before it is released itself. If the actor is idle, it can check before
+    // then, see an address is still around, and keep running. Instead, the wrappers share a guard
+    // which stops the actor when it is dropped.
+    let (stop_guard, from_where_clause) = match &args.stop_on_last_drop {
+        Some(stop_on_last_drop) => {
+            if args.expose_addr.is_some() {
+                abort!(
+                    stop_on_last_drop,
+                    "`stop_on_last_drop` cannot be used with `expose_addr`";
+                    help = "the wrapper holds a guard along with the address, so it can't be \
+                            built from the address with a struct literal"
+                );
+            }
+
+            let mut from_generics = generics.clone();
+            from_generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#actor: ::spaad::export::xtra::Handler<::spaad::export::Stop>));
+            let from_where_clause = from_generics.where_clause;
+
+            (Some(quote!(stop_guard)), quote!(#from_where_clause))
+        }
+        None => (None, quote!(#where_clause)),
+    };
+    let stop_guard_field = stop_guard
+        .as_ref()
+        .map(|guard| quote!(#guard: ::std::sync::Arc<::spaad::export::StopOnDrop>,));
+    let stop_guard_clone = stop_guard
+        .as_ref()
+        .map(|guard| quote!(#guard: self.#guard.clone(),));
+    let stop_guard_new = stop_guard
+        .as_ref()
+        .map(|guard| quote!(#guard: ::spaad::export::StopOnDrop::new(&addr),));
+
+    // A wrapper made from any address would get a guard of its own, rather than share the one of
+    // the actor's other wrappers, so with `stop_on_last_drop` only constructors make wrappers
+    let from_address = args.stop_on_last_drop.is_none().then(|| {
+        quote! {
+            #[automatically_derived]
+            impl#impl_generics From<::spaad::export::xtra::Address<#actor>>
+                for #ident#ty_generics
+            #where_clause {
+                fn from(addr: ::spaad::export::xtra::Address<#actor>) -> Self {
+                    Self { addr }
+                }
+            }
+        }
+    });
+
+    let weak = args
+        .weak
+        .as_ref()
+        .map(|_| weak_wrappers(&vis, &ident, &actor, &generics, stop_guard.as_ref()));
+
+    // Dropping the last wrapper only stops the actor for certain with `stop_on_last_drop`
+    let must_use = args.must_use.as_ref().map(|_| {
+        if args.stop_on_last_drop.is_some() {
+            quote!(#[must_use = "the actor is stopped once the last wrapper for it is dropped"])
+        } else {
+            quote!(#[must_use = "the actor can be stopped once the last wrapper for it is dropped"])
+        }
+    });
+
+    let mock = args
+        .mock
+        .as_ref()
+        .map(|_| mock_wrapper(&vis, &ident, &generics));
+
+    let subscribers = subscribers_for(&vis, &ident, &actor, &generics, subscriber_fields);
+
+    let builder = args.builder.as_ref().map(|builder| {
+        let fields = match &fields {
+            Fields::Named(fields) => fields,
+            _ => abort!(
+                builder,
+                "`builder` can only be used on structs with named fields"
+            ),
+        };
+        builder_for(
+            &vis,
+            &ident,
+            &actor,
+            &actor_struct,
+            &generics,
+            fields,
+            &defaults,
+        )
+    });
+
+    // The where clause of a tuple struct comes after its fields
+    let actor_body = match &fields {
+        Fields::Named(_) => quote!(#actor_where_clause #fields),
+        _ => quote!(#fields #actor_where_clause #semi_token),
+    };
+    let actor_def = if args.no_module.is_some() {
+        let hidden = args.actor_name.is_none().then(|| quote!(#[doc(hidden)]));
+        quote! {
+            #hidden
+            #(#attrs)*
+            #actor_vis struct #actor_ident#actor_generics #actor_body
+
+            #actor_alias
+        }
+    } else {
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            #actor_vis mod #actor_mod {
+                use super::*;
+
+                #(#attrs)*
+                pub struct #actor_ident#actor_generics #actor_body
+
+                #actor_alias
+            }
+        }
+    };
+
+    // A missing `Actor` impl is reported here first, since where clauses which don't hold are
+    // checked with the signatures of items, before their bodies. With type parameters, the clause
+    // is assumed instead, and the errors are left to the items which need it.
+    let assert_actor = format_ident!("__spaad_assert_{}_actor", ident);
+    let assert_where_clause = {
+        let mut generics = generics.clone();
+        let predicate = quote_spanned!(ident.span()=> #actor: ::spaad::export::IsActor);
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#predicate));
+        generics.where_clause
+    };
+
+    // `ready` is on every wrapper, so its handler is too, rather than only being generated along
+    // with an entangled `Actor` impl. It is bounded by the `Actor` impl, whichever bounds that has.
+    let ready_where_clause = {
+        let mut generics = generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#actor: ::spaad::export::xtra::Actor));
+        generics.where_clause
+    };
+
     quote! {
-        #vis struct #ident#impl_generics #where_clause {
-            addr: ::spaad::export::xtra::Address<#actor_mod::#ident#ty_generics>,
+        #[allow(dead_code, non_snake_case)]
+        fn #assert_actor#impl_generics() #assert_where_clause {}
+
+        #must_use
+        #(#wrapper_attrs)*
+        #vis struct #ident#generics #where_clause {
+            // Dropped first, so that the address it stops the actor through is still connected
+            #stop_guard_field
+            #addr_vis addr: ::spaad::export::xtra::Address<#actor>,
         }
 
+        #[automatically_derived]
         impl#impl_generics Clone for #ident#ty_generics #where_clause {
             fn clone(&self) -> Self {
-                Self { addr: self.addr.clone() }
+                Self {
+                    addr: self.addr.clone(),
+                    #stop_guard_clone
+                }
             }
         }
 
+        #[automatically_derived]
         impl#impl_generics #ident#ty_generics #where_clause {
             #vis fn address(
                 &self
-            ) -> &::spaad::export::xtra::Address<#actor_mod::#ident#ty_generics> {
+            ) -> &::spaad::export::xtra::Address<#actor> {
                 &self.addr
             }
 
+            #vis fn owned_address(
+                &self
+            ) -> ::spaad::export::xtra::Address<#actor> {
+                ::std::clone::Clone::clone(&self.addr)
+            }
+
+            // Nothing is handled before `Actor::started` has returned, so this resolves after it
+            #vis fn ready(
+                &self
+            ) -> impl ::std::future::Future<
+                Output = ::std::result::Result<(), ::spaad::export::xtra::Disconnected>,
+            > {
+                self.addr.send(::spaad::export::Ready)
+            }
+
             #vis fn into_address(
                 self
-            ) -> ::spaad::export::xtra::Address<#actor_mod::#ident#ty_generics> {
+            ) -> ::spaad::export::xtra::Address<#actor> {
                 self.addr
             }
         }
 
-        impl#impl_generics Into<::spaad::export::xtra::Address<#actor_mod::#ident#ty_generics>>
+        #[automatically_derived]
+        impl#impl_generics Into<::spaad::export::xtra::Address<#actor>>
             for #ident#ty_generics
         #where_clause {
-           fn into(self) -> ::spaad::export::xtra::Address<#actor_mod::#ident#ty_generics> {
+           fn into(self) -> ::spaad::export::xtra::Address<#actor> {
                 self.addr
            }
         }
 
-         impl#impl_generics From<::spaad::export::xtra::Address<#actor_mod::#ident#ty_generics>>
+        #[automatically_derived]
+        impl#impl_generics ::spaad::export::Spawned<#actor> for #ident#ty_generics
+        #from_where_clause {
+            fn spawned(addr: ::spaad::export::xtra::Address<#actor>) -> Self {
+                Self {
+                    #stop_guard_new
+                    addr,
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics ::spaad::export::Entangled for #ident#ty_generics #where_clause {
+            const NAME: &'static str = #name;
+        }
+
+        #[::spaad::export::async_trait::async_trait]
+        #[automatically_derived]
+        impl#impl_generics ::spaad::export::xtra::Handler<::spaad::export::Ready> for #actor
+            #ready_where_clause
+        {
+            async fn handle(
+                &mut self,
+                _: ::spaad::export::Ready,
+                _ctx: &mut ::spaad::export::xtra::Context<Self>,
+            ) {
+            }
+        }
+
+        #from_address
+
+        #display
+
+        #weak
+
+        #mock
+
+        #subscribers
+
+        #builder
+
+        #actor_def
+    }
+}
+
+/// Removes `#[spaad::subscribers]` from a field, returning it if it was there.
+fn take_subscribers_attr(attrs: &mut Vec<Attribute>) -> Option<Attribute> {
+    let is_subscribers = |attr: &Attribute| {
+        attr.path.segments.len() == 2
+            && attr.path.segments[0].ident == "spaad"
+            && attr.path.segments[1].ident == "subscribers"
+    };
+
+    let position = attrs.iter().position(is_subscribers)?;
+    let attr = attrs.remove(position);
+    if !attr.tokens.is_empty() {
+        abort!(attr, "`spaad::subscribers` does not take any arguments");
+    }
+
+    Some(attr)
+}
+
+/// The message `M` of a `Subscribers<M>` field.
+fn subscribers_msg(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(path) => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Subscribers" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(msg) => Some(msg),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Emits the handlers subscribing channels to the field marked `#[spaad::subscribers]` and
+/// unsubscribing them, along with the wrapper methods sending their messages.
+fn subscribers_for(
+    vis: &Visibility,
+    ident: &Ident,
+    actor: &proc_macro2::TokenStream,
+    generics: &Generics,
+    fields: Vec<(Member, Type, Attribute)>,
+) -> Option<proc_macro2::TokenStream> {
+    let mut fields = fields.into_iter();
+    let (member, ty, _) = fields.next()?;
+    if let Some((_, _, attr)) = fields.next() {
+        abort!(
+            attr,
+            "only one field of the actor can be marked `spaad::subscribers`"
+        );
+    }
+    let msg = match subscribers_msg(&ty) {
+        Some(msg) => msg,
+        None => abort!(
+            ty,
+            "fields marked `spaad::subscribers` must be a `spaad::Subscribers` of a message";
+            help = "declare it like `subscribers: spaad::Subscribers<Event>`"
+        ),
+    };
+
+    // The struct's generics may not be bounded by everything the `Actor` impl is
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let mut handler_generics = generics.clone();
+    handler_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#actor: ::spaad::export::xtra::Actor));
+    let handler_where = &handler_generics.where_clause;
+    let mut wrapper_generics = generics.clone();
+    wrapper_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote! {
+            #actor: ::spaad::export::xtra::Handler<::spaad::export::Subscribe<#msg>>
+                + ::spaad::export::xtra::Handler<::spaad::export::Unsubscribe>
+        });
+    let wrapper_where = &wrapper_generics.where_clause;
+    let channel = quote!(::spaad::export::xtra::prelude::MessageChannel<#msg>);
+    let disconnected = disconnected_panic();
+
+    Some(quote! {
+        #[::spaad::export::async_trait::async_trait]
+        #[automatically_derived]
+        impl#impl_generics ::spaad::export::xtra::Handler<::spaad::export::Subscribe<#msg>>
+            for #actor
+            #handler_where
+        {
+            async fn handle(
+                &mut self,
+                subscribe: ::spaad::export::Subscribe<#msg>,
+                _: &mut ::spaad::export::xtra::Context<Self>,
+            ) -> ::spaad::SubscriptionId {
+                self.#member.subscribe(subscribe.0)
+            }
+        }
+
+        #[::spaad::export::async_trait::async_trait]
+        #[automatically_derived]
+        impl#impl_generics ::spaad::export::xtra::Handler<::spaad::export::Unsubscribe>
+            for #actor
+            #handler_where
+        {
+            async fn handle(
+                &mut self,
+                unsubscribe: ::spaad::export::Unsubscribe,
+                _: &mut ::spaad::export::xtra::Context<Self>,
+            ) -> bool {
+                self.#member.unsubscribe(unsubscribe.0)
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics #ident#ty_generics #wrapper_where {
+            #[track_caller]
+            #vis fn subscribe(
+                &self,
+                channel: impl ::std::convert::Into<::std::boxed::Box<dyn #channel>>,
+            ) -> impl ::std::future::Future<Output = ::spaad::SubscriptionId> {
+                let __spaad_caller = ::std::panic::Location::caller();
+                let f = self.addr.send(::spaad::export::Subscribe(channel.into()));
+                async move { f.await#disconnected }
+            }
+
+            #[track_caller]
+            #vis fn unsubscribe(
+                &self,
+                id: ::spaad::SubscriptionId,
+            ) -> impl ::std::future::Future<Output = bool> {
+                let __spaad_caller = ::std::panic::Location::caller();
+                let f = self.addr.send(::spaad::export::Unsubscribe(id));
+                async move { f.await#disconnected }
+            }
+        }
+    })
+}
+
+/// Removes `#[spaad::default]` from a field, returning whether it was there. It is only allowed
+/// with `builder`, which is the only thing that reads it.
+fn take_default_attr(args: &EntangleArgs, attrs: &mut Vec<Attribute>) -> bool {
+    let is_default = |attr: &Attribute| {
+        attr.path.segments.len() == 2
+            && attr.path.segments[0].ident == "spaad"
+            && attr.path.segments[1].ident == "default"
+    };
+
+    let position = match attrs.iter().position(is_default) {
+        Some(position) => position,
+        None => return false,
+    };
+
+    let attr = attrs.remove(position);
+    if args.builder.is_none() {
+        abort!(
+            attr,
+            "`spaad::default` only has an effect with `#[spaad::entangled(builder)]`"
+        );
+    }
+    if !attr.tokens.is_empty() {
+        abort!(attr, "`spaad::default` does not take any arguments");
+    }
+
+    true
+}
+
+/// Emits `{Name}Builder`, which has a setter for each of the actor's fields and spawns the actor
+/// once they are set. Fields marked `#[spaad::default]` fall back to their `Default` value.
+fn builder_for(
+    vis: &Visibility,
+    ident: &Ident,
+    actor: &proc_macro2::TokenStream,
+    actor_struct: &proc_macro2::TokenStream,
+    generics: &Generics,
+    fields: &FieldsNamed,
+    defaults: &[bool],
+) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let builder = format_ident!("{}Builder", ident);
+    let names: Vec<_> = fields.named.iter().map(|field| &field.ident).collect();
+    let tys = fields.named.iter().map(|field| &field.ty);
+    let setter_tys = tys.clone();
+
+    let values = names.iter().zip(defaults).map(|(name, default)| {
+        if *default {
+            quote!(self.#name.unwrap_or_default())
+        } else {
+            let name_str = name.as_ref().unwrap().to_string();
+            quote! {
+                self.#name.ok_or(::spaad::MissingField(#name_str))?
+            }
+        }
+    });
+
+    quote! {
+        #vis struct #builder#generics #where_clause {
+            #(#names: ::std::option::Option<#tys>,)*
+        }
+
+        #[automatically_derived]
+        impl#impl_generics Default for #builder#ty_generics #where_clause {
+            fn default() -> Self {
+                #builder {
+                    #(#names: ::std::option::Option::None,)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics #builder#ty_generics #where_clause {
+            #(
+                #vis fn #names(mut self, #names: #setter_tys) -> Self {
+                    self.#names = ::std::option::Option::Some(#names);
+                    self
+                }
+            )*
+
+            /// Constructs the actor from the fields that were set and spawns it onto the spawner.
+            /// This fails if a field without `#[spaad::default]` was not set.
+            #vis fn build_and_spawn<ActorSpawner: ::spaad::export::xtra::spawn::Spawner>(
+                self,
+                actor_spawner: &mut ActorSpawner,
+            ) -> ::std::result::Result<#ident#ty_generics, ::spaad::MissingField>
+            where
+                #actor: ::spaad::export::xtra::Actor,
+                #ident#ty_generics: ::spaad::export::Spawned<#actor>,
+            {
+                use ::spaad::export::xtra::prelude::*;
+                let act = #actor_struct {
+                    #(#names: #values,)*
+                };
+                let addr = act.create(::std::option::Option::None).spawn(actor_spawner);
+                ::std::result::Result::Ok(::spaad::export::Spawned::spawned(addr))
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics #ident#ty_generics #where_clause {
+            #vis fn builder() -> #builder#ty_generics {
+                ::std::default::Default::default()
+            }
+        }
+    }
+}
+
+/// Emits the weak wrapper, `{Name}Weak`, which does not keep the actor alive, and `{Name}Handle`,
+/// which can hold either it or the strong wrapper. Their handler methods are emitted along with the
+/// wrapper's.
+fn weak_wrappers(
+    vis: &Visibility,
+    ident: &Ident,
+    actor: &proc_macro2::TokenStream,
+    generics: &Generics,
+    stop_guard: Option<&proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let weak = format_ident!("{}Weak", ident);
+    let handle = format_ident!("{}Handle", ident);
+
+    // With `stop_on_last_drop`, an upgraded wrapper must share the guard of the strong wrappers
+    // rather than make its own, so the weak wrapper holds onto it weakly too
+    let stop_guard_field =
+        stop_guard.map(|guard| quote!(#guard: ::std::sync::Weak<::spaad::export::StopOnDrop>,));
+    let stop_guard_clone = stop_guard.map(|guard| quote!(#guard: self.#guard.clone(),));
+    let stop_guard_downgrade =
+        stop_guard.map(|guard| quote!(#guard: ::std::sync::Arc::downgrade(&self.#guard),));
+    let stop_guard_upgrade = stop_guard.map(|guard| quote!(let #guard = self.#guard.upgrade();));
+    let upgraded = match stop_guard {
+        Some(guard) => quote! {
+            match (#guard, addr) {
+                (Some(#guard), Ok(Some(addr))) => Some(#ident { #guard, addr }),
+                _ => None,
+            }
+        },
+        None => quote! {
+            match addr {
+                Ok(Some(addr)) => Some(#ident { addr }),
+                _ => None,
+            }
+        },
+    };
+
+    quote! {
+        #vis struct #weak#generics #where_clause {
+            #stop_guard_field
+            addr: ::spaad::export::xtra::WeakAddress<#actor>,
+        }
+
+        #[automatically_derived]
+        impl#impl_generics Clone for #weak#ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                Self {
+                    addr: self.addr.clone(),
+                    #stop_guard_clone
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics #weak#ty_generics #where_clause {
+            #vis fn address(&self) -> &::spaad::export::xtra::WeakAddress<#actor> {
+                &self.addr
+            }
+
+            #vis fn upgrade(
+                &self
+            ) -> impl ::std::future::Future<Output = Option<#ident#ty_generics>> {
+                use ::spaad::export::xtra::prelude::*;
+
+                #stop_guard_upgrade
+                let f = self.addr.send(::spaad::export::Upgrade(::std::marker::PhantomData));
+                async move {
+                    let addr = f.await;
+                    #upgraded
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics #ident#ty_generics #where_clause {
+            #vis fn downgrade(&self) -> #weak#ty_generics {
+                #weak {
+                    #stop_guard_downgrade
+                    addr: self.addr.downgrade(),
+                }
+            }
+        }
+
+        // Upgrading goes through the actor, so it is only offered by the async `upgrade` rather
+        // than by a conversion
+        #[automatically_derived]
+        impl#impl_generics From<#ident#ty_generics> for #weak#ty_generics #where_clause {
+            fn from(wrapper: #ident#ty_generics) -> Self {
+                wrapper.downgrade()
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics Into<::spaad::export::xtra::WeakAddress<#actor>>
             for #ident#ty_generics
-         #where_clause {
-            fn from(addr: ::spaad::export::xtra::Address<#actor_mod::#ident#ty_generics>) -> Self {
-                Self { addr }
+        #where_clause {
+            fn into(self) -> ::spaad::export::xtra::WeakAddress<#actor> {
+                self.addr.downgrade()
             }
-         }
+        }
 
-        #[doc(hidden)]
-        #[allow(non_snake_case)]
-        #vis mod #actor_mod {
-            use super::*;
+        #vis enum #handle#generics #where_clause {
+            Strong(#ident#ty_generics),
+            Weak(#weak#ty_generics),
+        }
 
-            #(#attrs)*
-            pub struct #ident#impl_generics #where_clause #fields #semi_token
+        #[automatically_derived]
+        impl#impl_generics Clone for #handle#ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                match self {
+                    Self::Strong(strong) => Self::Strong(strong.clone()),
+                    Self::Weak(weak) => Self::Weak(weak.clone()),
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics #handle#ty_generics #where_clause {
+            #vis fn as_either(
+                &self
+            ) -> ::spaad::export::xtra::Address<#actor, ::spaad::export::xtra::refcount::Either> {
+                match self {
+                    Self::Strong(strong) => strong.addr.as_either(),
+                    Self::Weak(weak) => weak.addr.as_either(),
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics From<#ident#ty_generics> for #handle#ty_generics #where_clause {
+            fn from(strong: #ident#ty_generics) -> Self {
+                Self::Strong(strong)
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics From<#weak#ty_generics> for #handle#ty_generics #where_clause {
+            fn from(weak: #weak#ty_generics) -> Self {
+                Self::Weak(weak)
+            }
+        }
+    }
+}
+
+/// Emits `Mock{Name}` for tests, which records the calls to its handler methods rather than
+/// sending messages. Its handler methods are emitted along with the wrapper's.
+fn mock_wrapper(vis: &Visibility, ident: &Ident, generics: &Generics) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mock = format_ident!("Mock{}", ident);
+    let params = generics.type_params().map(|param| &param.ident);
+
+    quote! {
+        #[cfg(test)]
+        #[allow(dead_code)]
+        #vis struct #mock#generics #where_clause {
+            mock: ::spaad::mock::Mock,
+            _actor: ::std::marker::PhantomData<fn() -> (#(#params,)*)>,
+        }
+
+        #[cfg(test)]
+        #[automatically_derived]
+        impl#impl_generics Default for #mock#ty_generics #where_clause {
+            fn default() -> Self {
+                #mock {
+                    mock: ::std::default::Default::default(),
+                    _actor: ::std::marker::PhantomData,
+                }
+            }
+        }
+
+        #[cfg(test)]
+        #[allow(dead_code)]
+        #[automatically_derived]
+        impl#impl_generics #mock#ty_generics #where_clause {
+            #vis fn new() -> Self {
+                ::std::default::Default::default()
+            }
+
+            #vis fn calls(&self) -> ::std::sync::MutexGuard<'_, ::std::vec::Vec<::spaad::mock::Call>> {
+                self.mock.calls()
+            }
         }
     }
 }
 
-fn entangle_impl(impl_block: ItemImpl) -> proc_macro2::TokenStream {
+/// Splits the attributes of the actor struct into those to be put on the wrapper (written as
+/// `#[spaad::wrapper_attr(...)]`) and those to be left on the actor itself.
+fn split_wrapper_attrs(attrs: Vec<Attribute>) -> (Vec<proc_macro2::TokenStream>, Vec<Attribute>) {
+    let mut wrapper_attrs = Vec::new();
+    let mut actor_attrs = Vec::new();
+
+    for attr in attrs {
+        let is_wrapper_attr = attr.path.segments.len() == 2
+            && attr.path.segments[0].ident == "spaad"
+            && attr.path.segments[1].ident == "wrapper_attr";
+
+        if is_wrapper_attr {
+            let inner: proc_macro2::TokenStream = match attr.parse_args() {
+                Ok(inner) => inner,
+                Err(_) => abort!(attr, "expected `#[spaad::wrapper_attr(...)]`"),
+            };
+            if inner.is_empty() {
+                abort!(
+                    attr,
+                    "`spaad::wrapper_attr` needs an attribute to put on the wrapper"
+                );
+            }
+            // spaad always implements `Clone` for the wrapper, so deriving it would conflict
+            if let Ok(Meta::List(mut derive)) = syn::parse2::<Meta>(inner.clone()) {
+                if derive.path.is_ident("derive") {
+                    derive.nested = derive
+                        .nested
+                        .into_iter()
+                        .filter(|nested| {
+                            !matches!(nested, NestedMeta::Meta(Meta::Path(path)) if is_clone(path))
+                        })
+                        .collect();
+                    if !derive.nested.is_empty() {
+                        wrapper_attrs.push(quote!(#[#derive]));
+                    }
+                    continue;
+                }
+            }
+            wrapper_attrs.push(quote!(#[#inner]));
+        } else {
+            actor_attrs.push(attr);
+        }
+    }
+
+    (wrapper_attrs, actor_attrs)
+}
+
+/// Whether the path names `Clone`, either alone or by its full path.
+fn is_clone(path: &Path) -> bool {
+    let segments: Vec<_> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+    segments == ["Clone"]
+        || segments == ["std", "clone", "Clone"]
+        || segments == ["core", "clone", "Clone"]
+}
+
+fn entangle_impl(args: &EntangleArgs, mut impl_block: ItemImpl) -> proc_macro2::TokenStream {
     if !matches!(*impl_block.self_ty, Type::Path(_)) {
         abort!(
             impl_block,
@@ -147,9 +1565,164 @@ fn entangle_impl(impl_block: ItemImpl) -> proc_macro2::TokenStream {
         );
     }
 
-    match &impl_block.trait_ {
-        Some(_) => entangle_trait_impl(impl_block),
-        None => entangle_handlers_impl(impl_block),
+    // The wrapper can only be named with the bounds its struct is declared with
+    let self_ty = impl_block.self_ty.clone();
+    add_send_bounds(&mut impl_block.generics, &self_ty);
+
+    // The self type must still name the same wrapper as the path does, however it is spelled
+    let written = impl_block.self_ty.clone();
+    retarget_self_ty(args, &mut impl_block.self_ty);
+    if impl_block.trait_.is_none() {
+        spell_out_self(args, &mut impl_block);
+    }
+    let assert_same_wrapper = args.actor.as_ref().map(|_| {
+        let wrapper = &impl_block.self_ty;
+        let (impl_generics, _, where_clause) = impl_block.generics.split_for_impl();
+        let cfgs = impl_block
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("cfg"));
+        quote_spanned! {written.span()=>
+            #(#cfgs)*
+            const _: () = {
+                #[allow(dead_code)]
+                fn __spaad_assert_same_wrapper#impl_generics(wrapper: #written) -> #wrapper
+                    #where_clause
+                {
+                    wrapper
+                }
+            };
+        }
+    });
+
+    // The actor is looked for by the name written as the self type, which is wrong for a type
+    // alias. That can only be told once the type is resolved, so it is checked in a constant.
+    let assert_own_name = match (&args.actor, get_name_from_ty(&written)) {
+        (None, Some(name)) if impl_block.generics.params.is_empty() => {
+            let cfgs = impl_block
+                .attrs
+                .iter()
+                .filter(|attr| attr.path.is_ident("cfg"));
+            let message = format!(
+                "`{}` is not the name of the struct of its actor, as it would be for a type \
+                 alias; pass the actor it stands for with `actor = \"...\"`",
+                name
+            );
+            let name = name.to_string();
+            Some(quote_spanned! {written.span()=>
+                #(#cfgs)*
+                const _: () = ::std::assert!(
+                    ::spaad::export::same_name(
+                        <#written as ::spaad::export::Entangled>::NAME,
+                        #name,
+                    ),
+                    #message,
+                );
+            })
+        }
+        _ => None,
+    };
+
+    let expanded = match &impl_block.trait_ {
+        Some(_) => {
+            args.check_for_trait_impl();
+            entangle_trait_impl(args, impl_block)
+        }
+        None => {
+            args.check_for_impl();
+            entangle_handlers_impl(args, impl_block)
+        }
+    };
+
+    quote! {
+        #expanded
+
+        #assert_same_wrapper
+
+        #assert_own_name
+    }
+}
+
+/// Replaces `Self` in the where clause of a handlers impl with the actor. The clause is also put
+/// on the impls for the wrapper and on free functions, where `Self` would be something else.
+fn spell_out_self(args: &EntangleArgs, impl_block: &mut ItemImpl) {
+    fn replace_self(
+        tokens: proc_macro2::TokenStream,
+        actor: &proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        tokens
+            .into_iter()
+            .map(|token| match token {
+                proc_macro2::TokenTree::Ident(ident) if ident == "Self" => actor.clone(),
+                proc_macro2::TokenTree::Group(group) => {
+                    let stream = replace_self(group.stream(), actor);
+                    let mut replaced = proc_macro2::Group::new(group.delimiter(), stream);
+                    replaced.set_span(group.span());
+                    quote!(#replaced)
+                }
+                token => quote!(#token),
+            })
+            .collect()
+    }
+
+    let where_clause = match &impl_block.generics.where_clause {
+        Some(where_clause) => where_clause,
+        None => return,
+    };
+    let name = get_name(impl_block).clone();
+    let mut actor = impl_block.self_ty.clone();
+    if let Type::Path(path) = &mut *actor {
+        transform_actor_path(args, &name, &mut path.path);
+    }
+
+    let replaced = replace_self(quote!(#where_clause), &quote!(#actor));
+    impl_block.generics.where_clause = Some(parse_quote!(#replaced));
+}
+
+/// Bounds each type parameter used in the self type by `Send + 'static`, which xtra requires of
+/// the actor and its messages, so that they needn't be written out on every impl block of a
+/// generic actor. The bounds are added to wherever the parameter is already bounded, skipping any
+/// already there.
+fn add_send_bounds(generics: &mut Generics, self_ty: &Type) {
+    fn add_missing(bounds: &mut Punctuated<TypeParamBound, Token![+]>) {
+        let has_send = bounds.iter().any(|bound| match bound {
+            TypeParamBound::Trait(bound) => get_name_from_path(&bound.path) == "Send",
+            TypeParamBound::Lifetime(_) => false,
+        });
+        let has_static = bounds.iter().any(|bound| match bound {
+            TypeParamBound::Lifetime(lifetime) => lifetime.ident == "static",
+            TypeParamBound::Trait(_) => false,
+        });
+        if !has_send {
+            bounds.push(parse_quote!(::std::marker::Send));
+        }
+        if !has_static {
+            bounds.push(parse_quote!('static));
+        }
+    }
+
+    let mut predicates = generics
+        .where_clause
+        .iter_mut()
+        .flat_map(|where_clause| where_clause.predicates.iter_mut())
+        .filter_map(|predicate| match predicate {
+            WherePredicate::Type(predicate) => Some(predicate),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let params = generics.params.iter_mut().filter_map(|param| match param {
+        GenericParam::Type(param) if mentions(quote!(#self_ty), &param.ident) => Some(param),
+        _ => None,
+    });
+    for param in params {
+        let is_param = |ty: &Type| matches!(ty, Type::Path(ty) if ty.path.is_ident(&param.ident));
+        let predicate = predicates
+            .iter_mut()
+            .find(|pred| is_param(&pred.bounded_ty));
+        match predicate {
+            Some(predicate) => add_missing(&mut predicate.bounds),
+            None => add_missing(&mut param.bounds),
+        }
     }
 }
 
@@ -158,13 +1731,13 @@ fn get_name_from_path(p: &Path) -> &proc_macro2::Ident {
 }
 
 fn get_name_from_ty(ty: &syn::Type) -> Option<&proc_macro2::Ident> {
-    match &*ty {
+    match ty {
         Type::Path(path) => Some(get_name_from_path(&path.path)),
         _ => None,
     }
 }
 
-fn ty_is_name(ty: &syn::Type, name: &str) -> bool {
+pub fn ty_is_name(ty: &syn::Type, name: &str) -> bool {
     get_name_from_ty(ty).map(|id| id == name).unwrap_or(false)
 }
 
@@ -179,7 +1752,7 @@ fn get_name(block: &ItemImpl) -> &proc_macro2::Ident {
     get_name_from_path(self_ty_path)
 }
 
-fn get_actor_name(block: &ItemImpl) -> proc_macro2::TokenStream {
+fn get_actor_name(args: &EntangleArgs, block: &ItemImpl) -> proc_macro2::TokenStream {
     let self_ty_path = match &*block.self_ty {
         Type::Path(path) => &path.path,
         _ => abort!(
@@ -196,15 +1769,20 @@ fn get_actor_name(block: &ItemImpl) -> proc_macro2::TokenStream {
         ident: mod_name,
         arguments: PathArguments::None,
     });
-    path.segments.push(PathSegment {
-        ident: name.clone(),
-        arguments: PathArguments::None,
-    });
+    if args.no_module.is_none() {
+        path.segments.push(PathSegment {
+            ident: name.clone(),
+            arguments: PathArguments::None,
+        });
+    }
 
     quote!(#path)
 }
 
-fn entangle_handlers_impl(mut handlers_impl: ItemImpl) -> proc_macro2::TokenStream {
+fn entangle_handlers_impl(
+    args: &EntangleArgs,
+    mut handlers_impl: ItemImpl,
+) -> proc_macro2::TokenStream {
     let old_impl = handlers_impl.clone();
     let name = get_name(&handlers_impl).clone();
     let wrapper = match &*handlers_impl.self_ty {
@@ -217,7 +1795,7 @@ fn entangle_handlers_impl(mut handlers_impl: ItemImpl) -> proc_macro2::TokenStre
     };
     let actor_path = match &mut *handlers_impl.self_ty {
         Type::Path(ref mut path) => {
-            transform_actor_path(&name, &mut path.path);
+            transform_actor_path(args, &name, &mut path.path);
 
             let mut actor_path = path.path.clone();
             actor_path.segments.last_mut().unwrap().arguments = PathArguments::None;
@@ -225,20 +1803,73 @@ fn entangle_handlers_impl(mut handlers_impl: ItemImpl) -> proc_macro2::TokenStre
         }
         _ => unreachable!(),
     };
+    let actor_import = if args.no_module.is_some() {
+        quote!(#actor_path as #name)
+    } else {
+        quote!(#actor_path)
+    };
     let actor = handlers_impl.self_ty.clone();
 
     let (impl_generics, _, where_clause) = handlers_impl.generics.split_for_impl();
+
     let actor_items = handlers_impl.items.clone();
-    let transformed_items = transform_items(&old_impl, handlers_impl.items.iter());
+
+    // Other attributes (including attribute macros) are kept on the actor's impl, since that is
+    // where the code that was written lives. `cfg`s must apply to everything that is generated.
+    // Unlike the generated impls, it is not `#[automatically_derived]`, so that tools such as
+    // coverage still count the handlers themselves.
+    let actor_attrs = &handlers_impl.attrs;
+    let cfgs: Vec<_> = actor_attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .collect();
+
+    // An `inner_only` impl block holds helpers for the actor's own use, so it is left as it is
+    let wrapper_impl = if let Some(inner_only) = &args.inner_only {
+        check_inner_only(args, inner_only, &old_impl);
+        None
+    } else {
+        check_renames(&old_impl);
+        let transformed_items =
+            transform_items(args, &old_impl, &actor, handlers_impl.items.iter());
+        let api = args
+            .api
+            .as_ref()
+            .map(|lit| api_trait(args, lit, &handlers_impl, &old_impl, &wrapper, &cfgs));
+        let handler_list = args
+            .handlers
+            .as_ref()
+            .map(|lit| handler_list(lit, &old_impl));
+        let (senders, into_parts) = args
+            .senders
+            .as_ref()
+            .map(|lit| senders_struct(args, lit, &handlers_impl, &old_impl, &wrapper, &cfgs))
+            .unzip();
+
+        Some(quote! {
+            #(#cfgs)*
+            #[automatically_derived]
+            impl#impl_generics #wrapper #where_clause {
+                #handler_list
+                #into_parts
+                #(#transformed_items)*
+            }
+
+            #api
+            #senders
+        })
+    };
+
     quote! {
-        impl#impl_generics #wrapper #where_clause {
-            #(#transformed_items)*
-        }
+        #wrapper_impl
 
+        #(#cfgs)*
         const _: () = {
             #[allow(unused_imports)]
-            use #actor_path;
+            use #actor_import;
 
+            #(#actor_attrs)*
+            #[allow(clippy::new_without_default)]
             impl#impl_generics #actor #where_clause {
                 #(#actor_items)*
             }
@@ -246,33 +1877,270 @@ fn entangle_handlers_impl(mut handlers_impl: ItemImpl) -> proc_macro2::TokenStre
     }
 }
 
+/// Emits the object safe trait named by `api`, which has an async method for each of the public
+/// handlers of the impl block, and implements it for the wrapper.
+fn api_trait(
+    args: &EntangleArgs,
+    api: &LitStr,
+    handlers_impl: &ItemImpl,
+    old_impl: &ItemImpl,
+    wrapper: &Path,
+    cfgs: &[&Attribute],
+) -> proc_macro2::TokenStream {
+    let api: Ident = match api.parse() {
+        Ok(ident) => ident,
+        Err(_) => abort!(api, "Expected API trait name to be a valid identifier"),
+    };
+    let (impl_generics, ty_generics, where_clause) = handlers_impl.generics.split_for_impl();
+    let (decls, impls): (Vec<_>, Vec<_>) = old_impl
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(method) => api_method(args, method),
+            _ => None,
+        })
+        .unzip();
+    let async_trait = args
+        .native_async
+        .is_none()
+        .then(|| quote!(#[::spaad::export::async_trait::async_trait]));
+
+    quote! {
+        #(#cfgs)*
+        #async_trait
+        pub trait #api#impl_generics: Send + Sync #where_clause {
+            #(#decls)*
+        }
+
+        #(#cfgs)*
+        #async_trait
+        #[automatically_derived]
+        impl#impl_generics #api#ty_generics for #wrapper #where_clause {
+            #(#impls)*
+        }
+    }
+}
+
+/// Emits the struct named by `senders`, which has a field with a closure for each of the public
+/// handlers of the impl block, along with the wrapper's `into_parts` method which creates it.
+fn senders_struct(
+    args: &EntangleArgs,
+    senders: &LitStr,
+    handlers_impl: &ItemImpl,
+    old_impl: &ItemImpl,
+    wrapper: &Path,
+    cfgs: &[&Attribute],
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let senders: Ident = match senders.parse() {
+        Ok(ident) => ident,
+        Err(_) => abort!(
+            senders,
+            "Expected the name of the senders struct to be a valid identifier"
+        ),
+    };
+    let (impl_generics, ty_generics, where_clause) = handlers_impl.generics.split_for_impl();
+    let actor = &handlers_impl.self_ty;
+    let senders_fields: Vec<_> = old_impl
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(method) => sender_field(method, args.catch_panics.is_some()),
+            _ => None,
+        })
+        .collect();
+    // The struct can be used wherever one of its fields can
+    let vis = widest_visibility(senders_fields.iter().map(|(vis, _, _)| vis));
+    let fields = senders_fields.iter().map(|(_, field, _)| field);
+    let inits = senders_fields.iter().map(|(_, _, init)| init);
+
+    // The wrapper names every generic parameter of the impl block, which the senders might not
+    let senders_struct = quote! {
+        #(#cfgs)*
+        #[allow(clippy::type_complexity)]
+        #vis struct #senders#impl_generics #where_clause {
+            #(#fields)*
+            _wrapper: ::std::marker::PhantomData<fn() -> #wrapper>,
+        }
+    };
+    let into_parts = quote! {
+        /// Splits the wrapper into the address of the actor, and a closure to send each of the
+        /// messages of the handlers in this impl block.
+        #vis fn into_parts(self) -> (::spaad::export::xtra::Address<#actor>, #senders#ty_generics) {
+            let senders = #senders {
+                #(#inits)*
+                _wrapper: ::std::marker::PhantomData,
+            };
+            (self.address().clone(), senders)
+        }
+    };
+
+    (senders_struct, into_parts)
+}
+
+/// Emits the constant named by `handlers`, which lists the names of the wrapper methods of the
+/// handlers of the impl block, in the order they are declared.
+fn handler_list(handlers: &LitStr, old_impl: &ItemImpl) -> proc_macro2::TokenStream {
+    let handlers: Ident = match handlers.parse() {
+        Ok(ident) => ident,
+        Err(_) => abort!(
+            handlers,
+            "Expected the name of the handler list to be a valid identifier"
+        ),
+    };
+    let entries = old_impl.items.iter().flat_map(|item| match item {
+        ImplItem::Method(method) => handler_list_entries(method),
+        _ => Vec::new(),
+    });
+
+    quote! {
+        /// The names of the handlers declared in this impl block.
+        pub const #handlers: &'static [&'static str] = &[#(#entries),*];
+    }
+}
+
 fn transform_items<'a, I: Iterator<Item = &'a ImplItem> + 'a>(
+    args: &'a EntangleArgs,
     impl_block: &'a ItemImpl,
+    actor: &'a Type,
     iter: I,
 ) -> impl Iterator<Item = proc_macro2::TokenStream> + 'a {
     iter.map(move |method| match method {
-        ImplItem::Const(c) => quote!(#c),
+        ImplItem::Const(c) => transform_const(actor, c),
         ImplItem::Type(t) => quote!(#t),
         ImplItem::Macro(m) => quote!(#m),
         ImplItem::Verbatim(v) => quote!(#v),
-        ImplItem::Method(m) => transform_method(impl_block, m.clone()),
+        ImplItem::Method(m) => transform_method(args, impl_block, m.clone()),
         _ => unimplemented!("Unknown impl item"),
     })
 }
 
-fn transform_actor_path(name: &Ident, path: &mut Path) {
+/// Points the path to the wrapper at the actor instead, which is in a module named after the
+/// wrapper, or named like that module itself with `no_module`.
+fn transform_actor_path(args: &EntangleArgs, name: &Ident, path: &mut Path) {
     let mod_name = format_ident!("__{}Actor", name);
-    let last = path.segments.pop().unwrap().into_tuple().0;
-    path.segments.push(PathSegment::from(mod_name));
+    let mut last = path.segments.pop().unwrap().into_tuple().0;
+    if args.no_module.is_some() {
+        last.ident = mod_name;
+    } else {
+        path.segments.push(PathSegment::from(mod_name));
+    }
     path.segments.push(last)
 }
 
-fn entangle_trait_impl(mut trait_impl: ItemImpl) -> proc_macro2::TokenStream {
+/// Rejects what would generate something for the wrapper in an `inner_only` impl block.
+fn check_inner_only(args: &EntangleArgs, inner_only: &Ident, block: &ItemImpl) {
+    if args.fire_and_forget.is_some()
+        || args.catch_panics.is_some()
+        || args.handlers.is_some()
+        || args.api.is_some()
+        || args.senders.is_some()
+        || !args.messages.is_empty()
+    {
+        abort!(
+            inner_only,
+            "`inner_only` cannot be used with `fire_and_forget`, `catch_panics`, `handlers`, `api`, \
+             `senders` or `messages`";
+            help = "they only change what is generated for the wrapper"
+        );
+    }
+
+    let spaad_attr = block
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(method) => Some(&method.attrs),
+            _ => None,
+        })
+        .flatten()
+        .find(|attr| attr.path.segments.len() == 2 && attr.path.segments[0].ident == "spaad");
+    if let Some(attr) = spaad_attr {
+        abort!(
+            attr,
+            "handlers and constructors cannot be declared in an `inner_only` impl block";
+            help = "nothing is generated for the wrapper from it, so move them to another impl block"
+        );
+    }
+}
+
+fn entangle_trait_impl(args: &EntangleArgs, mut trait_impl: ItemImpl) -> proc_macro2::TokenStream {
     let name = get_name(&trait_impl).clone();
     match &mut *trait_impl.self_ty {
-        Type::Path(ref mut path) => transform_actor_path(&name, &mut path.path),
+        Type::Path(ref mut path) => transform_actor_path(args, &name, &mut path.path),
         _ => unreachable!(),
     }
 
-    quote!(#trait_impl)
+    // Lets `spaad::shutdown_all` stop the actor from outside, since xtra's addresses cannot, and
+    // `reattach` methods replace its state
+    let is_actor = trait_impl
+        .trait_
+        .as_ref()
+        .map(|(_, path, _)| get_name_from_path(path) == "Actor")
+        .unwrap_or(false);
+    let stop = if is_actor {
+        let actor = &trait_impl.self_ty;
+        let (impl_generics, _, where_clause) = trait_impl.generics.split_for_impl();
+        let cfgs: Vec<_> = trait_impl
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("cfg"))
+            .collect();
+
+        // Weak wrappers are upgraded through the actor, since only it can get a strong address from
+        // a weak one. As it handles one message at a time, it can't stop partway through. Whether
+        // the struct has them isn't known here, so this is generated either way.
+        Some(quote! {
+            #(#cfgs)*
+            #[::spaad::export::async_trait::async_trait]
+            #[automatically_derived]
+            impl#impl_generics ::spaad::export::xtra::Handler<::spaad::export::Upgrade<Self>>
+                for #actor
+                #where_clause
+            {
+                async fn handle(
+                    &mut self,
+                    _: ::spaad::export::Upgrade<Self>,
+                    ctx: &mut ::spaad::export::xtra::Context<Self>,
+                ) -> Option<::spaad::export::xtra::Address<Self>> {
+                    ctx.address().ok()
+                }
+            }
+
+            #(#cfgs)*
+            #[::spaad::export::async_trait::async_trait]
+            #[automatically_derived]
+            impl#impl_generics ::spaad::export::xtra::Handler<::spaad::export::Stop> for #actor
+                #where_clause
+            {
+                async fn handle(
+                    &mut self,
+                    _: ::spaad::export::Stop,
+                    ctx: &mut ::spaad::export::xtra::Context<Self>,
+                ) {
+                    ctx.stop();
+                }
+            }
+
+            #(#cfgs)*
+            #[::spaad::export::async_trait::async_trait]
+            #[automatically_derived]
+            impl#impl_generics ::spaad::export::xtra::Handler<::spaad::export::Reattach<Self>>
+                for #actor
+                #where_clause
+            {
+                async fn handle(
+                    &mut self,
+                    reattach: ::spaad::export::Reattach<Self>,
+                    ctx: &mut ::spaad::export::xtra::Context<Self>,
+                ) {
+                    let mut old = ::std::mem::replace(self, reattach.0);
+                    ::spaad::export::xtra::Actor::stopped(&mut old).await;
+                    ::spaad::export::xtra::Actor::started(self, ctx).await;
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    quote!(#trait_impl #stop)
 }
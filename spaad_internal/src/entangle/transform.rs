@@ -1,7 +1,7 @@
-use crate::entangle::{get_actor_name, get_name, ty_is_name};
+use crate::entangle::{get_actor_name, get_name, ty_is_name, EntangleArgs};
 use proc_macro2::TokenStream;
 use proc_macro_error::abort;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use std::collections::HashMap;
 use std::ops::Deref;
 use proc_macro2::TokenStream as TokenStream2;
@@ -38,7 +38,9 @@ fn parse_attributes(attrs: &[Attribute]) -> AttrMap {
         let nested = match nested {
             Some(nested) => nested,
             None => {
-                attributes.insert(path.segments.last().unwrap().ident.to_string(), None);
+                attributes
+                    .entry(path.segments.last().unwrap().ident.to_string())
+                    .or_insert(None);
                 continue;
             }
         };
@@ -46,7 +48,15 @@ fn parse_attributes(attrs: &[Attribute]) -> AttrMap {
         for entry in nested {
             let (key_path, value) = match entry {
                 NestedMeta::Meta(Meta::NameValue(meta)) => (meta.path, meta.lit),
-                _ => abort!(entry, "Inner should be name value meta"),
+                // #[spaad::thing(flag)] is treated as #[spaad::thing(flag = true)]
+                NestedMeta::Meta(Meta::Path(path)) => {
+                    let lit = Lit::Bool(LitBool {
+                        value: true,
+                        span: path.span(),
+                    });
+                    (path, lit)
+                }
+                _ => abort!(entry, "Inner should be name value meta or a path"),
             };
 
             let ident = key_path
@@ -57,9 +67,11 @@ fn parse_attributes(attrs: &[Attribute]) -> AttrMap {
 
             let list_map = attributes
                 .entry(path.segments.last().unwrap().ident.to_string())
-                .or_insert_with(|| Some(HashMap::new()));
+                .or_insert(None);
 
-            list_map.as_mut().unwrap().insert(ident_str, (ident, value));
+            list_map
+                .get_or_insert_with(HashMap::new)
+                .insert(ident_str, (ident, value));
         }
     }
 
@@ -92,20 +104,153 @@ fn is_context_ref(ty_ref: &TypeReference) -> bool {
     is_context
 }
 
+/// The message type a handler takes, if it is one of those listed with
+/// `#[spaad::entangled(messages(...))]`. This is the case if its only argument (other than the
+/// context) is a type with the same name as one of them.
+fn predefined_message(args: &EntangleArgs, sig: &Signature) -> Option<Path> {
+    if args.messages.is_empty() {
+        return None;
+    }
+
+    let mut msg_args = sig.inputs.iter().skip(1).filter_map(|arg| match arg {
+        FnArg::Typed(PatType { ty, .. }) => match &**ty {
+            Type::Reference(ty_ref) if is_context_ref(ty_ref) => None,
+            ty => Some(ty),
+        },
+        FnArg::Receiver(_) => None,
+    });
+
+    let ty = match (msg_args.next(), msg_args.next()) {
+        (Some(Type::Path(ty)), None) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+    let name = &ty.segments.last()?.ident;
+
+    args.messages
+        .iter()
+        .any(|msg| &msg.segments.last().unwrap().ident == name)
+        .then(|| ty.clone())
+}
+
+/// Whether the argument is a receiver with an explicit type, such as `self: Pin<&mut Self>`.
+fn is_typed_self(arg: &FnArg) -> bool {
+    match arg {
+        FnArg::Typed(PatType { pat, .. }) => {
+            matches!(&**pat, Pat::Ident(pat) if pat.ident == "self")
+        }
+        FnArg::Receiver(_) => false,
+    }
+}
+
+fn is_spaad_meta(meta: &NestedMeta) -> bool {
+    match meta {
+        NestedMeta::Meta(meta) => meta
+            .path()
+            .segments
+            .first()
+            .map(|seg| seg.ident == "spaad")
+            .unwrap_or(false),
+        NestedMeta::Lit(_) => false,
+    }
+}
+
+/// spaad's attributes are read before the compiler evaluates any `cfg_attr`s, so a spaad attribute
+/// inside of a `cfg_attr` would be missed. Instead, such a method is split into two copies: one
+/// with the spaad attributes applied, gated on the predicate, and one without, gated on its
+/// negation. Only one `cfg_attr` containing spaad attributes is supported per method.
+fn split_cfg_attr(method: &ImplItemMethod) -> Option<[ImplItemMethod; 2]> {
+    let mut found = None;
+
+    for (idx, attr) in method.attrs.iter().enumerate() {
+        if !attr.path.is_ident("cfg_attr") {
+            continue;
+        }
+
+        let nested = match attr.parse_meta() {
+            Ok(Meta::List(list)) => list.nested,
+            _ => abort!(attr, "expected `#[cfg_attr(predicate, attributes...)]`"),
+        };
+
+        if !nested.iter().skip(1).any(is_spaad_meta) {
+            continue;
+        }
+
+        if found.is_some() {
+            abort!(
+                attr,
+                "only one `cfg_attr` containing spaad attributes is supported on a method"
+            );
+        }
+
+        found = Some((idx, nested));
+    }
+
+    let (idx, nested) = found?;
+    let mut nested = nested.into_iter();
+    let predicate = nested.next().unwrap();
+    let (spaad_attrs, other_attrs): (Vec<_>, Vec<_>) = nested.partition(is_spaad_meta);
+
+    let mut with = method.clone();
+    let mut without = method.clone();
+
+    let replacement: Vec<Attribute> = if other_attrs.is_empty() {
+        Vec::new()
+    } else {
+        vec![parse_quote!(#[cfg_attr(#predicate, #(#other_attrs),*)])]
+    };
+
+    with.attrs.splice(idx..=idx, replacement.clone());
+    without.attrs.splice(idx..=idx, replacement);
+
+    with.attrs.push(parse_quote!(#[cfg(#predicate)]));
+    with.attrs.extend(
+        spaad_attrs
+            .into_iter()
+            .map(|meta| -> Attribute { parse_quote!(#[#meta]) }),
+    );
+    without.attrs.push(parse_quote!(#[cfg(not(#predicate))]));
+
+    Some([with, without])
+}
+
 // I know, I apologize.
-pub fn transform_method(impl_block: &ItemImpl, method: ImplItemMethod) -> proc_macro2::TokenStream {
-    let name = get_name(&impl_block);
-    let actor_name = get_actor_name(&impl_block);
-    let act_ty_generics = impl_block.generics.split_for_impl().1;
+pub fn transform_method(
+    args: &EntangleArgs,
+    impl_block: &ItemImpl,
+    method: ImplItemMethod,
+) -> proc_macro2::TokenStream {
+    if let Some([with, without]) = split_cfg_attr(&method) {
+        let with = transform_method(args, impl_block, with);
+        let without = transform_method(args, impl_block, without);
+        return quote!(#with #without);
+    }
+
+    let name = get_name(impl_block);
+    let actor_name = get_actor_name(args, impl_block);
+    let act_ty_generics = actor_generic_args(impl_block);
 
     let attrs = parse_attributes(&method.attrs);
 
     let handler_attrs = attrs.get("handler");
     let is_handler = handler_attrs.is_some();
 
+    // `self: Pin<&mut Self>` and the like are typed arguments, so they must not be mistaken for the
+    // first argument of a static method
+    let typed_self = method.sig.inputs.first().filter(|arg| is_typed_self(arg));
+    if let (Some(receiver), true) = (typed_self, is_handler) {
+        abort!(
+            receiver,
+            "this receiver is not supported by handlers";
+            help = "handlers must take `&self` or `&mut self`, which the actor is borrowed as \
+                    when a message is handled"
+        );
+    }
+
     if !is_handler {
         // matches checks for no receiver, i.e a static method
-        return if matches!(method.sig.inputs.first(), Some(FnArg::Typed(_)) | None) {
+        return if typed_self.is_none()
+            && matches!(method.sig.inputs.first(), Some(FnArg::Typed(_)) | None)
+        {
             transform_static_methods(
                 name,
                 actor_name,
@@ -113,18 +258,85 @@ pub fn transform_method(impl_block: &ItemImpl, method: ImplItemMethod) -> proc_m
                 attrs.contains_key("spawn"),
                 method,
                 &attrs,
-                act_ty_generics,
+                &act_ty_generics,
             )
         } else {
             quote!()
         };
     }
 
-    // TODO: throw an error for other attribute keys
+    // Nothing marks it safe to call when handling a message, which any caller could send
+    if let Some(unsafety) = &method.sig.unsafety {
+        abort!(
+            unsafety,
+            "handlers cannot be `unsafe`";
+            help = "make the handler safe, and call the `unsafe` function from inside of it"
+        );
+    }
+
+    // xtra's mailbox is a single queue, so a message sent from outside can't be handled later than
+    // the ones sent after it
+    let background = handler_attrs
+        .unwrap()
+        .as_ref()
+        .and_then(|attrs| attrs.get("background"));
+    if let Some((background, _)) = background {
+        abort!(
+            background,
+            "handlers cannot be sent with a lower priority, since xtra's mailbox has none";
+            help = "messages are handled in the order they are sent, so send it after the others; \
+                    for work to do in the background, have the handler do a little at a time and \
+                    send the rest to `ctx.address()`, so it waits behind other messages"
+        );
+    }
+
+    // Any other key would be silently ignored, such as a misspelt flag
+    if let Some(attrs) = handler_attrs.unwrap() {
+        let mut keys: Vec<_> = attrs.iter().collect();
+        keys.sort_by_key(|(key, _)| *key);
+        let unknown = keys
+            .into_iter()
+            .find(|(key, _)| !HANDLER_KEYS.contains(&key.as_str()));
+        if let Some((_, (ident, _))) = unknown {
+            abort!(
+                ident,
+                "Expected one of `send`, `broadcast`, `batch`, `stream`, `into`, `arc_args`, \
+                 `guard`, `coalesce`, `coalesce_key`, `defer`, `msg`, `rename`"
+            );
+        }
+    }
+
     let reuse_msg = handler_attrs
         .unwrap()
         .as_ref()
-        .and_then(|attrs| attrs.get("msg").map(|(_, lit)| lit.clone()));
+        .and_then(|attrs| attrs.get("msg").map(|(_, lit)| lit_string_to_path(lit)))
+        .or_else(|| predefined_message(args, &method.sig));
+    let has_flag = |flag| {
+        handler_attrs
+            .unwrap()
+            .as_ref()
+            .map(|attrs| attrs.contains_key(flag))
+            .unwrap_or(false)
+    };
+    let force_send = has_flag("send");
+    let broadcast = has_flag("broadcast");
+    let batch = has_flag("batch");
+    let stream = has_flag("stream");
+    let into = has_flag("into");
+    let arc_args = has_flag("arc_args");
+    let wrapper_name = get_handler_name(&method.sig, handler_attrs.unwrap());
+    let guard = get_guard(handler_attrs.unwrap());
+    let coalesce = get_coalesce(handler_attrs.unwrap());
+    let in_api = (args.api.is_some() && is_api_method(&method))
+        || (args.senders.is_some() && is_sender_method(&method));
+
+    // A `send` on the handler always wins over `fire_and_forget` on the impl block, and a handler
+    // taking a `Reply` always responds
+    let do_send = args.fire_and_forget.is_some()
+        && !force_send
+        && returns_unit(&method.sig.output)
+        && reply_arg(&method.sig).is_none();
+    let catch_panics = args.catch_panics.is_some();
 
     let ImplItemMethod {
         attrs,
@@ -134,36 +346,62 @@ pub fn transform_method(impl_block: &ItemImpl, method: ImplItemMethod) -> proc_m
     } = method;
 
     match sig.inputs.first_mut() {
-        Some(FnArg::Typed(_)) | None => {
+        Some(FnArg::Typed(first)) => {
             abort!(
-                sig.inputs,
+                first,
                 "handlers in `spaad::entangled` impl blocks must take `self`"
             );
         }
+        None => {
+            abort!(
+                sig.ident,
+                "handlers in `spaad::entangled` impl blocks must take `self`"
+            );
+        }
+        Some(FnArg::Receiver(recv)) if recv.reference.is_none() => {
+            abort!(
+                recv,
+                "handlers cannot take `self` by value";
+                help = "handlers must take `&self` or `&mut self`, which the actor is borrowed as \
+                        when a message is handled"
+            );
+        }
         Some(FnArg::Receiver(recv)) => {
             recv.mutability = None;
         }
     }
 
+    let arced = prepare_message_inputs(&mut sig, arc_args);
+    let reply = reply_arg(&sig);
+    let deferred = deferred_output(&mut sig, handler_attrs.unwrap());
+    if let (Some(_), Some(catch_panics)) = (&deferred, &args.catch_panics) {
+        abort!(
+            catch_panics,
+            "handlers with `defer` or a `Reply` cannot be in an impl block which catches panics";
+            help = "the response is computed after the handler has returned, where its panics \
+                    can't be caught"
+        );
+    }
+    if let (Some(_), Some(guard)) = (&deferred, &guard) {
+        abort!(
+            guard,
+            "handlers with `defer` or a `Reply` cannot have a `guard`";
+            help = "a rejected message is answered with the default of the return type, which a \
+                    deferred response has none of"
+        );
+    }
+
     let call_inputs = sig
         .inputs
         .iter()
-        .cloned()
         .skip(1)
+        .cloned()
         .map(|arg| {
-            let span = arg.span();
             if let FnArg::Typed(mut pat_type) = arg {
                 if let Pat::Ident(ref mut pat) = &mut *pat_type.pat {
                     pat.mutability = None;
                 }
 
-                if !matches!(*pat_type.pat, Pat::Type(_) | Pat::Ident(_)) {
-                    abort!(
-                        span,
-                        "`spaad::entangle` only support simple patterns (e.g `mut f: f64`)"
-                    )
-                }
-
                 return pat_type;
             }
             unreachable!()
@@ -173,32 +411,95 @@ pub fn transform_method(impl_block: &ItemImpl, method: ImplItemMethod) -> proc_m
                 return !is_context_ref(ty_ref);
             }
 
-            true
+            reply_ty(ty).is_none()
         });
 
     let msg_members = call_inputs.clone();
+    let msg_member_tys: Vec<_> = msg_members.clone().map(|PatType { ty, .. }| ty).collect();
+    // Errors about an argument which can't be sent would otherwise point at the generated message
+    let assert_sendable = msg_member_tys
+        .iter()
+        .map(|ty| quote_spanned!(ty.span()=> ::spaad::export::assert_sendable::<#ty>();));
     let msg_members_destructured: Vec<_> =
         msg_members.clone().map(|PatType { pat, .. }| pat).collect();
     let output = &sig.output;
+    if let ReturnType::Type(_, ty) = output {
+        if let Type::ImplTrait(_) = &**ty {
+            abort!(
+                ty,
+                "handlers cannot return `impl Trait`, since the type must be named in the message";
+                help = "to return a stream, return the receiving half of a channel (e.g \
+                        `futures::channel::mpsc::Receiver<T>`) and keep the sender in the actor"
+            );
+        }
+
+        // The response outlives the handler's borrows of the actor and its arguments, since it is
+        // sent back to the caller afterwards
+        if let Some(borrow) = find_borrow(ty) {
+            abort!(
+                borrow,
+                "handlers can only return references which are `'static`";
+                help = "the response is sent to the caller after the handler has returned, so \
+                        it can't borrow from the actor or the message; return an owned value \
+                        (e.g `String` rather than `&str`) instead"
+            );
+        }
+    }
+
+    // `Self` would refer to the wrapper in the message and to the actor in the handler. Neither
+    // makes sense to send in a message, so it is rejected instead of causing a type mismatch.
+    let self_in_sig = msg_members
+        .clone()
+        .map(|PatType { ty, .. }| find_self(quote!(#ty)))
+        .chain(std::iter::once(find_self(quote!(#output))))
+        .flatten()
+        .next();
+    if let Some(self_ty) = self_in_sig {
+        abort!(
+            self_ty,
+            "`Self` cannot be used in the arguments or return type of a handler";
+            help = "the actor's state cannot be sent in a message; pass the data it needs instead"
+        );
+    }
+
     let (handle_result, output, result) = match transform_ret(&sig.output) {
-        Some(output) => (quote! { .and_then(|x| x) }, quote!(#output), output),
-        None if matches!(output, ReturnType::Default) => (
-            quote! { .expect("actor disconnected") },
-            quote!(()),
-            quote!(()),
+        Some(_) if catch_panics => abort!(
+            sig.output,
+            "handlers which catch panics cannot return `Result<T, Disconnected>`";
+            help = "they always return `Result<T, spaad::HandlerError>`, which reports \
+                    disconnection as `HandlerError::Disconnected`, so return `T` instead"
         ),
+        None if catch_panics => {
+            let (output, flatten) = fallible_output(&sig.output, true);
+            (quote!(#flatten), output.clone(), output)
+        }
+        Some(output) => (quote! { .and_then(|x| x) }, quote!(#output), output),
+        None if matches!(output, ReturnType::Default) => {
+            (disconnected_panic(), quote!(()), quote!(()))
+        }
         None => {
             let output = match output {
                 ReturnType::Type(_, ty) => ty,
                 _ => unreachable!(),
             };
 
+            (disconnected_panic(), quote!(#output), quote!(#output))
+        }
+    };
+    // The actor answers with the deferred response, which the wrapper method then waits for. It
+    // can also report that the actor disconnected, such as when a `Reply` is dropped
+    let (handle_result, output) = match &deferred {
+        Some(ty) => {
+            let settle = match transform_ret(&parse_quote!(-> #ty)) {
+                Some(_) => quote!(.and_then(|x| x)),
+                None => disconnected_panic(),
+            };
             (
-                quote! { .expect("actor disconnected") },
-                quote!(#output),
-                quote!(#output),
+                quote!(.unwrap_or_else(::spaad::export::disconnected).await #settle),
+                quote!(#ty),
             )
         }
+        None => (handle_result, output),
     };
     let fn_name = &sig.ident;
     let mut ctx_idx: Option<usize> = None;
@@ -216,17 +517,46 @@ pub fn transform_method(impl_block: &ItemImpl, method: ImplItemMethod) -> proc_m
                     }
                 }
 
-                true
+                reply_ty(ty).is_none()
             }
             _ => true,
         })
         .map(|(_, x)| x)
         .collect();
 
+    // The API trait must be object safe, so its methods always take the message's own types
+    let api_inputs = fn_decl_inputs.clone();
+
+    // With `into`, the wrappers accept anything converting into each argument, and convert it
+    // before the message is built
+    let (fn_decl_inputs, conversions) = if into {
+        let inputs = fn_decl_inputs
+            .into_iter()
+            .map(|arg| match arg {
+                FnArg::Typed(mut pat_type) => {
+                    let ty = &pat_type.ty;
+                    pat_type.ty = parse_quote!(impl ::std::convert::Into<#ty>);
+                    FnArg::Typed(pat_type)
+                }
+                recv => recv,
+            })
+            .collect();
+        let conversions = quote! {
+            #(let #msg_members_destructured = ::std::convert::Into::into(#msg_members_destructured);)*
+        };
+
+        (inputs, Some(conversions))
+    } else {
+        (fn_decl_inputs, None)
+    };
+
     let mut call_inputs: Vec<proc_macro2::TokenStream> = call_inputs
         .map(|PatType { pat, .. }| {
             if let Pat::Ident(id) = &*pat {
                 let id = &id.ident;
+                if arced.contains(id) {
+                    return quote!(&*#id);
+                }
                 return quote!(#id);
             }
             unreachable!("{:?}", pat)
@@ -234,15 +564,67 @@ pub fn transform_method(impl_block: &ItemImpl, method: ImplItemMethod) -> proc_m
         .collect();
 
     if reuse_msg.is_some() && call_inputs.len() != 1 {
-        abort!(
-            call_inputs[0],
-            "you can only pass one arg when reusing a message"
-        );
+        let extra = call_inputs
+            .get(1)
+            .cloned()
+            .unwrap_or_else(|| quote!(#fn_name));
+        abort!(extra, "you can only pass one arg when reusing a message");
     }
 
-    if let Some(ctx_idx) = ctx_idx {
+    // A rejected message is answered with the default of the handler's return type, without the
+    // handler running at all
+    let guard = guard.map(|guard| {
+        quote! {
+            if !self.#guard(#(&#msg_members_destructured),*) {
+                return ::std::default::Default::default();
+            }
+        }
+    });
+
+    // A coalescing handler drops a message if it has started for the same key since the message
+    // was sent, answering it just like a message rejected by a guard
+    let coalesce = coalesce.map(|(field, key)| {
+        if reuse_msg.is_some() {
+            abort!(
+                field,
+                "handlers reusing a message cannot be coalesced";
+                help = "the message must be generated, so that it can record when it was sent"
+            );
+        }
+        let key = match key {
+            Some(key) => {
+                let is_arg = msg_members_destructured
+                    .iter()
+                    .any(|pat| matches!(&**pat, Pat::Ident(arg) if arg.ident == key));
+                if !is_arg {
+                    abort!(key, "`{}` is not an argument of the handler", key);
+                }
+                quote!(::std::clone::Clone::clone(&#key))
+            }
+            None => quote!(()),
+        };
+        quote! {
+            if !self.#field.start(#key, __spaad_sequence) {
+                return ::std::default::Default::default();
+            }
+        }
+    });
+    let sequence = coalesce.as_ref().map(|_| quote!(__spaad_sequence));
+    let sequence_binding = sequence.as_ref().map(|field| quote!(#field,));
+    let sequence_field = sequence.as_ref().map(|field| quote!(#field: u64,));
+    let sequence_init = sequence
+        .as_ref()
+        .map(|field| quote!(#field: ::spaad::export::sequence(),));
+
+    // The context and reply aren't in the message, so they are put back in order
+    let mut local_inputs: Vec<_> = ctx_idx.map(|idx| (idx, quote!(ctx))).into_iter().collect();
+    if let Some((idx, reply, _)) = &reply {
+        local_inputs.push((*idx, quote!(#reply)));
+    }
+    local_inputs.sort_by_key(|(idx, _)| *idx);
+    for (idx, input) in local_inputs {
         // -1 because 0 is self
-        call_inputs.insert(ctx_idx - 1, quote!(ctx))
+        call_inputs.insert(idx - 1, input)
     }
 
     let (fn_impl_generics, fn_ty_generics, fn_where) = sig.generics.split_for_impl();
@@ -264,6 +646,40 @@ pub fn transform_method(impl_block: &ItemImpl, method: ImplItemMethod) -> proc_m
 
     let (handler_impl_generics, _, handler_where) = handler_generics.split_for_impl();
 
+    // The message is declared inside of the wrapper method, which can't use the impl block's type
+    // parameters, so a message naming any of them is generic over the handler's parameters too.
+    // Those which only appear in its response or bounds are used by a phantom field.
+    let msg_tokens = quote!(#(#msg_member_tys)* #result #fn_where);
+    let msg_generics = if impl_block
+        .generics
+        .type_params()
+        .any(|param| mentions(msg_tokens.clone(), &param.ident))
+    {
+        &handler_generics
+    } else {
+        &sig.generics
+    };
+    let (msg_impl_generics, msg_ty_generics, msg_where) = msg_generics.split_for_impl();
+    let msg_turbo = msg_ty_generics.as_turbofish();
+    let phantom = (msg_generics.params.len() > sig.generics.params.len()).then(|| {
+        let params = msg_generics.params.iter().filter_map(|param| match param {
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(quote!(#ident))
+            }
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                Some(quote!(&#lifetime ()))
+            }
+            GenericParam::Const(_) => None,
+        });
+        quote!(::std::marker::PhantomData<fn() -> (#(#params,)*)>)
+    });
+    let phantom_field = phantom.as_ref().map(|ty| quote!(__spaad_params: #ty,));
+    let phantom_init = phantom
+        .as_ref()
+        .map(|_| quote!(__spaad_params: ::std::marker::PhantomData,));
+
     let await_ = if sig.asyncness.is_some() {
         Some(quote!(.await))
     } else {
@@ -272,110 +688,1164 @@ pub fn transform_method(impl_block: &ItemImpl, method: ImplItemMethod) -> proc_m
 
     let responder = quote!();
 
+    let ret = if do_send {
+        None
+    } else {
+        Some(quote!(-> impl ::std::future::Future<Output = #output>))
+    };
+
     let async_trait = quote!(#[::spaad::export::async_trait::async_trait]);
 
-    if let Some(lit) = reuse_msg {
-        let msg = lit_string_to_path(&lit);
+    let call = quote!(#guard #coalesce self.#fn_name#fn_turbo(#(#call_inputs),*)#await_);
+    let call = match (&reply, &deferred) {
+        (Some((_, reply, _)), _) => quote! {
+            let (#reply, response) = ::spaad::export::reply_channel();
+            #call;
+            response
+        },
+        (None, Some(_)) => quote!(::spaad::Deferred::new(#call)),
+        (None, None) => call,
+    };
+    // The actor is stopped after a panic unless it is kept alive, since the panic may have left
+    // its state half updated
+    let call = if catch_panics {
+        let stop = args.keep_alive.is_none().then(|| {
+            quote! {
+                if handled.is_err() {
+                    ctx.stop();
+                }
+            }
+        });
+        quote! {
+            let handled = ::spaad::export::catch_panic(async { #call }).await;
+            #stop
+            handled
+        }
+    } else {
+        call
+    };
+
+    // The message type and its construction, the handler, and how to construct the message from
+    // clones of the arguments when broadcasting. The handler's return is the response as is, which
+    // xtra moves into the caller's channel, so it's never cloned, even for broadcasts
+    let (msg_defs, msg_ty, msg, msg_cloned, handle) = if let Some(msg_ty) = &reuse_msg {
         let msg_arg = call_inputs[0].clone();
 
         let handle = quote! {
             async fn handle(
                 &mut self,
-                #msg_arg: #msg,
+                #msg_arg: #msg_ty,
                 ctx: &mut ::spaad::export::xtra::Context<Self>,
             ) -> #result {
-                self.#fn_name#fn_turbo(#(#call_inputs),*)#await_
+                #call
             }
         };
 
-        let handler = quote! {
-            #async_trait
-            #[allow(unused_variables)]
-            impl#handler_impl_generics
-                ::spaad::export::xtra::Handler<#msg>
-            for #actor_name#act_ty_generics
-                 #handler_where
-            {
-                #responder #handle
+        (
+            None,
+            quote!(#msg_ty),
+            quote!(#msg_arg),
+            quote!(::std::clone::Clone::clone(&#msg_arg)),
+            handle,
+        )
+    } else {
+        let actor_str = name.to_string();
+        let handler_str = fn_name.to_string();
+        let handle = quote! {
+            async fn handle(
+                &mut self,
+                m: Msg#msg_ty_generics,
+                ctx: &mut ::spaad::export::xtra::Context<Self>,
+            ) -> #result {
+                let Msg { #(#msg_members_destructured,)* __spaad_sent_at, #sequence_binding .. } = m;
+                __spaad_sent_at.record(#actor_str, #handler_str);
+                #call
             }
         };
 
-        quote! {
-            #[allow(unused_mut)]
-            #(#attrs)* #vis fn #fn_name#fn_impl_generics(
-                #(#fn_decl_inputs),*
-            ) -> impl std::future::Future<Output = #output>
-                #fn_where
+        let msg_defs = quote! {
+            struct Msg#msg_impl_generics #msg_where {
+                #(#msg_members,)*
+                __spaad_sent_at: ::spaad::export::SentAt,
+                #sequence_field
+                #phantom_field
+            };
+
+            #[automatically_derived]
+            impl#msg_impl_generics ::spaad::export::xtra::Message for Msg#msg_ty_generics
+                #msg_where
             {
-                use ::spaad::export::xtra::prelude::*;
+                type Result = #result;
+            }
+        };
 
-                #handler
+        let msg = quote! {
+            Msg#msg_turbo {
+                #(#msg_members_destructured,)*
+                __spaad_sent_at: ::spaad::export::SentAt::now(),
+                #sequence_init
+                #phantom_init
+            }
+        };
 
-                let f = self.addr.send(#msg_arg);
-                async { f.await#handle_result }
+        let msg_cloned = quote! {
+            Msg#msg_turbo {
+                #(#msg_members_destructured: ::std::clone::Clone::clone(&#msg_members_destructured),)*
+                __spaad_sent_at: ::spaad::export::SentAt::now(),
+                #sequence_init
+                #phantom_init
             }
+        };
+
+        (
+            Some(msg_defs),
+            quote!(Msg#msg_ty_generics),
+            msg,
+            msg_cloned,
+            handle,
+        )
+    };
 
+    let handler = quote! {
+        #async_trait
+        #[allow(unused_variables, non_local_definitions)]
+        #[automatically_derived]
+        impl#handler_impl_generics
+            ::spaad::export::xtra::Handler<#msg_ty>
+        for #actor_name#act_ty_generics
+             #handler_where
+        {
+            #responder #handle
         }
+    };
+
+    let (fallible_output, flatten) = match &deferred {
+        Some(ty) => fallible_output(&parse_quote!(-> #ty), false),
+        None => fallible_output(&sig.output, catch_panics),
+    };
+    // What the future `fut` of a send which reports disconnection resolves to
+    let fallible_response = if deferred.is_some() {
+        quote!(::spaad::export::settle(fut.await).await#flatten)
     } else {
-        let handle = quote! {
-            async fn handle(
-                &mut self,
-                m: Msg#fn_ty_generics,
-                ctx: &mut ::spaad::export::xtra::Context<Self>,
-            ) -> #result {
-                let Msg { #(#msg_members_destructured),* } = m;
-                self.#fn_name#fn_turbo(#(#call_inputs),*)#await_
+        quote!(fut.await#flatten)
+    };
+
+    // Callers are warned about a deprecated handler through each of its methods, but the code
+    // generated inside of them uses the handler and items nested in them, which are deprecated too
+    let allow_deprecated = is_deprecated(&attrs).then(|| quote!(#[allow(deprecated)]));
+    // The methods generated alongside a hidden handler's wrapper method are hidden with it
+    let forwarded: Vec<_> = attrs
+        .iter()
+        .filter(|attr| {
+            attr.path.is_ident("cfg") || attr.path.is_ident("deprecated") || is_doc_hidden(attr)
+        })
+        .collect();
+
+    let broadcast = if broadcast {
+        let all_name = format_ident!("{}_all", wrapper_name);
+        let do_send_all_name = format_ident!("{}_do_send_all", wrapper_name);
+        let mut all_generics = sig.generics.clone();
+        all_generics.params.insert(0, parse_quote!('__spaad_actors));
+        let all_impl_generics = all_generics.split_for_impl().0;
+        let inputs: Vec<_> = fn_decl_inputs.iter().skip(1).collect();
+        // A predefined message is handled once for the actor, by the wrapper method
+        let handler = msg_defs.as_ref().map(|_| &handler);
+        let collect = if deferred.is_some() {
+            quote! {
+                let responses = responses.into_iter().map(|res| async move {
+                    ::spaad::export::settle(res).await#flatten
+                });
+                ::spaad::export::futures_util::future::join_all(responses).await
             }
+        } else {
+            quote!(responses.into_iter().map(|res| res#flatten).collect())
         };
 
-        let handler = quote! {
-            #async_trait
-            #[allow(unused_variables)]
-            impl#handler_impl_generics
-                ::spaad::export::xtra::Handler<Msg#fn_ty_generics>
-            for #actor_name#act_ty_generics
-                 #handler_where
+        Some(quote! {
+            #(#forwarded)*
+            #[allow(unused_mut)]
+            #allow_deprecated
+            #vis fn #all_name#all_impl_generics(
+                actors: impl ::std::iter::IntoIterator<Item = &'__spaad_actors Self>,
+                #(#inputs),*
+            ) -> impl ::std::future::Future<Output = ::std::vec::Vec<#fallible_output>>
+                #fn_where
             {
-                #responder #handle
+                use ::spaad::export::xtra::prelude::*;
+
+                #msg_defs
+
+                #handler
+
+                #conversions
+                let sends: ::std::vec::Vec<_> = actors
+                    .into_iter()
+                    .map(|actor| actor.addr.send(#msg_cloned))
+                    .collect();
+
+                async move {
+                    let responses = ::spaad::export::futures_util::future::join_all(sends).await;
+                    #collect
+                }
             }
-        };
 
-        quote! {
+            // Best effort: an actor which has disconnected doesn't stop the rest from being sent to
+            #(#forwarded)*
             #[allow(unused_mut)]
-            #(#attrs)* #vis fn #fn_name#fn_impl_generics(
-                #(#fn_decl_inputs),*
-            ) -> impl std::future::Future<Output = #output>
+            #allow_deprecated
+            #vis fn #do_send_all_name#all_impl_generics(
+                actors: impl ::std::iter::IntoIterator<Item = &'__spaad_actors Self>,
+                #(#inputs),*
+            ) -> ::std::vec::Vec<::std::result::Result<(), ::spaad::export::xtra::Disconnected>>
                 #fn_where
             {
                 use ::spaad::export::xtra::prelude::*;
 
-                struct Msg#fn_impl_generics #fn_where { #(#msg_members),* };
+                #msg_defs
 
-                impl#fn_impl_generics ::spaad::export::xtra::Message for Msg#fn_ty_generics
-                    #fn_where
-                {
-                    type Result = #result;
-                }
+                #handler
+
+                #conversions
+                actors
+                    .into_iter()
+                    .map(|actor| actor.addr.do_send(#msg_cloned))
+                    .collect()
+            }
+        })
+    } else {
+        None
+    };
+
+    // A stream of inputs can be piped through a handler taking one argument, each being sent once
+    // the response to the one before has arrived so that the outputs are in the same order
+    let stream = if stream {
+        let flag = &handler_attrs.unwrap().as_ref().unwrap()["stream"].0;
+        if do_send {
+            abort!(
+                flag,
+                "handlers in a `fire_and_forget` impl block cannot be streamed";
+                help = "there would be no responses to wait for; mark the handler with `send`"
+            );
+        } else if msg_members_destructured.len() != 1 {
+            abort!(flag, "only handlers taking one argument can be streamed");
+        }
+
+        let stream_name = format_ident!("{}_stream", wrapper_name);
+        let mut stream_generics = sig.generics.clone();
+        stream_generics.params.push(parse_quote!(
+            __SpaadInputs: ::spaad::export::futures_util::stream::Stream<Item = #(#msg_member_tys)*>
+        ));
+        let stream_impl_generics = stream_generics.split_for_impl().0;
+        let input = &msg_members_destructured[0];
+        // A predefined message is handled once for the actor, by the wrapper method
+        let handler = msg_defs.as_ref().map(|_| &handler);
+
+        Some(quote! {
+            #(#forwarded)*
+            #allow_deprecated
+            #vis fn #stream_name#stream_impl_generics(
+                &self,
+                inputs: __SpaadInputs,
+            ) -> impl ::spaad::export::futures_util::stream::Stream<Item = #fallible_output>
+                #fn_where
+            {
+                use ::spaad::export::futures_util::stream::StreamExt;
+                use ::spaad::export::xtra::prelude::*;
+
+                #msg_defs
 
                 #handler
 
-                let f = self.addr.send(Msg#fn_turbo { #(#msg_members_destructured),* });
-                async { f.await#handle_result }
+                let addr = self.addr.clone();
+                inputs.then(move |#input| {
+                    let fut = addr.send(#msg);
+                    async move { #fallible_response }
+                })
             }
+        })
+    } else {
+        None
+    };
+
+    // A batch of arguments is sent in a single message, which the actor unrolls by handling each
+    // of them in turn, so that the mailbox is only gone through once for all of them
+    let batch = if batch {
+        let flag = &handler_attrs.unwrap().as_ref().unwrap()["batch"].0;
+        if reuse_msg.is_some() {
+            abort!(
+                flag,
+                "handlers reusing a message cannot be batched";
+                help = "send a `Vec` of the message to a handler taking it instead"
+            );
+        }
+        if reply.is_some() || deferred.is_some() {
+            abort!(
+                flag,
+                "handlers with deferred responses cannot be batched";
+                help = "each response would be waited for in turn, which batching can't speed up"
+            );
+        }
+        if catch_panics {
+            abort!(
+                flag,
+                "handlers which catch panics cannot be batched";
+                help = "the rest of the batch would be handled after a panic had left the actor's \
+                        state half updated"
+            );
+        }
+        if msg_members_destructured.is_empty() {
+            abort!(flag, "only handlers taking arguments can be batched");
         }
-    }
-}
 
-fn transform_ret(r: &ReturnType) -> Option<proc_macro2::TokenStream> {
-    if let ReturnType::Type(_, ret_ty) = r {
-        if ty_is_name(ret_ty, "Result") {
-            if let Type::Path(ty_path) = ret_ty.deref() {
-                let arg = &ty_path.path.segments.last().unwrap().arguments;
-                if let PathArguments::AngleBracketed(generics) = arg {
-                    let last = generics.args.last().unwrap();
-                    if let GenericArgument::Type(ty) = last {
-                        if ty_is_name(ty, "Disconnected") {
-                            return Some(quote!(#ret_ty));
+        let batch_name = format_ident!("{}_batch", wrapper_name);
+        let (input_ty, input) = match (&msg_member_tys[..], &msg_members_destructured[..]) {
+            ([ty], [input]) => (quote!(#ty), quote!(#input)),
+            (tys, inputs) => (quote!((#(#tys,)*)), quote!((#(#inputs,)*))),
+        };
+        let actor_str = name.to_string();
+        let handler_str = fn_name.to_string();
+
+        Some(quote! {
+            #(#forwarded)*
+            #allow_deprecated
+            #vis fn #batch_name#fn_impl_generics(
+                &self,
+                inputs: ::std::vec::Vec<#input_ty>,
+            ) -> impl ::std::future::Future<
+                Output = ::std::result::Result<
+                    ::std::vec::Vec<#result>,
+                    ::spaad::export::xtra::Disconnected,
+                >,
+            >
+                #fn_where
+            {
+                use ::spaad::export::xtra::prelude::*;
+
+                #msg_defs
+
+                struct Batch#msg_impl_generics(::std::vec::Vec<Msg#msg_ty_generics>) #msg_where;
+
+                #[automatically_derived]
+                impl#msg_impl_generics ::spaad::export::xtra::Message for Batch#msg_ty_generics
+                    #msg_where
+                {
+                    type Result = ::std::vec::Vec<#result>;
+                }
+
+                #async_trait
+                #[allow(unused_variables, non_local_definitions)]
+                #[automatically_derived]
+                impl#handler_impl_generics
+                    ::spaad::export::xtra::Handler<Batch#msg_ty_generics>
+                for #actor_name#act_ty_generics
+                     #handler_where
+                {
+                    #[allow(clippy::redundant_async_block)]
+                    async fn handle(
+                        &mut self,
+                        batch: Batch#msg_ty_generics,
+                        ctx: &mut ::spaad::export::xtra::Context<Self>,
+                    ) -> ::std::vec::Vec<#result> {
+                        let mut responses = ::std::vec::Vec::with_capacity(batch.0.len());
+                        for m in batch.0 {
+                            let Msg {
+                                #(#msg_members_destructured,)* __spaad_sent_at, #sequence_binding ..
+                            } = m;
+                            __spaad_sent_at.record(#actor_str, #handler_str);
+                            // A guard returns from this block, rejecting only its own message
+                            responses.push(async { #call }.await);
+                        }
+                        responses
+                    }
+                }
+
+                let batch = inputs.into_iter().map(|#input| #msg).collect();
+                self.addr.send(Batch#msg_turbo(batch))
+            }
+        })
+    } else {
+        None
+    };
+
+    // Since the message is defined elsewhere, the wrapper can be used as a channel for it
+    let channel_conversion = reuse_msg.is_some().then(|| {
+        let wrapper = &impl_block.self_ty;
+        let channel = quote!(::spaad::export::xtra::prelude::MessageChannel<#msg_ty>);
+        quote! {
+            #[allow(non_local_definitions)]
+            #[automatically_derived]
+            impl#handler_impl_generics ::std::convert::From<#wrapper>
+                for ::std::boxed::Box<dyn #channel>
+                #handler_where
+            {
+                fn from(wrapper: #wrapper) -> Self {
+                    ::std::boxed::Box::new(wrapper.addr)
+                }
+            }
+        }
+    });
+
+    // It can also be sent through a closure, which is stored without knowing the actor's type
+    let sender = reuse_msg.is_some().then(|| {
+        let sender_name = format_ident!("{}_sender", wrapper_name);
+        quote! {
+            #(#forwarded)*
+            #allow_deprecated
+            #vis fn #sender_name#fn_impl_generics(&self) -> ::spaad::Sender<#msg_ty>
+                #fn_where
+            {
+                let wrapper = ::std::clone::Clone::clone(self);
+                ::std::boxed::Box::new(move |msg| ::std::boxed::Box::pin(wrapper.addr.send(msg)))
+            }
+        }
+    });
+
+    // The weak wrapper and handle enum are defined by the struct, so they get their methods from
+    // here, where they can use the message and handler defined in the wrapper's method
+    let handles = if args.weak.is_some() {
+        let (impl_generics, _, where_clause) = impl_block.generics.split_for_impl();
+        let handle_ty = rename_self_ty(impl_block, |name| format_ident!("{}Handle", name));
+        let weak_ty = rename_self_ty(impl_block, |name| format_ident!("{}Weak", name));
+        let inputs = fn_decl_inputs.iter().skip(1);
+        let weak_inputs = inputs.clone();
+
+        Some(quote! {
+            #[allow(non_local_definitions)]
+            #[automatically_derived]
+            impl#impl_generics #handle_ty #where_clause {
+                #[allow(unused_mut)]
+                #(#attrs)* #vis fn #wrapper_name#fn_impl_generics(
+                    &self,
+                    #(#inputs),*
+                ) -> impl ::std::future::Future<Output = #fallible_output>
+                    #fn_where
+                {
+                    #conversions
+                    let fut = self.as_either().send(#msg);
+                    async move { #fallible_response }
+                }
+            }
+
+            #[allow(non_local_definitions)]
+            #[automatically_derived]
+            impl#impl_generics #weak_ty #where_clause {
+                #[allow(unused_mut)]
+                #allow_deprecated
+                #(#attrs)* #vis fn #wrapper_name#fn_impl_generics(
+                    &self,
+                    #(#weak_inputs),*
+                ) -> impl ::std::future::Future<Output = #fallible_output>
+                    #fn_where
+                {
+                    <#handle_ty>::Weak(self.clone())
+                        .#wrapper_name#fn_turbo(#(#msg_members_destructured),*)
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    // The mock records the call and responds with what the test set, rather than sending anything
+    let mock = if args.mock.is_some() {
+        let (impl_generics, _, where_clause) = impl_block.generics.split_for_impl();
+        let mock_ty = rename_self_ty(impl_block, |name| format_ident!("Mock{}", name));
+        let on_name = format_ident!("on_{}", wrapper_name);
+        let handler_str = wrapper_name.to_string();
+        let inputs = fn_decl_inputs.iter().skip(1);
+        let cfgs: Vec<_> = attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("cfg"))
+            .collect();
+
+        let default = if returns_unit(&sig.output) && catch_panics {
+            quote!(.unwrap_or(Ok(())))
+        } else if returns_unit(&sig.output) {
+            quote!(.unwrap_or_default())
+        } else {
+            let msg = format!(
+                "`{}` was called on a mock without a response set with `{}`",
+                wrapper_name, on_name
+            );
+            quote!(.unwrap_or_else(|| ::std::panic!(#msg)))
+        };
+        let respond = quote! {
+            let response = self
+                .mock
+                .call::<_, #result>(#handler_str, (#(#msg_members_destructured,)*))
+                #default;
+        };
+        // Like the wrapper method, the mock only panics on disconnection if the handler doesn't
+        // report it, though a response set with `Deferred::new` never does
+        let respond_await = deferred.as_ref().map(|ty| match transform_ret(&parse_quote!(-> #ty)) {
+            Some(_) => quote!(.await.and_then(|x| x)),
+            None => quote!(.await.expect("the deferred response of the mock reported disconnection")),
+        });
+        let (ret, body) = if do_send {
+            (None, respond)
+        } else {
+            (
+                Some(quote!(-> impl ::std::future::Future<Output = #output>)),
+                quote!(#respond async move { response#respond_await }),
+            )
+        };
+        let body = quote!(#conversions #body);
+
+        Some(quote! {
+            #[cfg(test)]
+            #[allow(non_local_definitions, dead_code)]
+            #[automatically_derived]
+            impl#impl_generics #mock_ty #where_clause {
+                #(#cfgs)*
+                #vis fn #on_name#fn_impl_generics(
+                    &self,
+                    respond: impl FnMut(&(#(#msg_member_tys,)*)) -> #result + Send + 'static,
+                ) #fn_where {
+                    self.mock.set_response(#handler_str, respond)
+                }
+
+                #[allow(unused_mut)]
+                #allow_deprecated
+                #(#forwarded)*
+                #vis fn #wrapper_name#fn_impl_generics(
+                    &self,
+                    #(#inputs),*
+                ) #ret
+                    #fn_where
+                {
+                    #body
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    // The API trait and senders are implemented outside of this method, so they send the message
+    // through this
+    let api_send = if in_api {
+        let (impl_generics, _, where_clause) = impl_block.generics.split_for_impl();
+        let self_ty = &impl_block.self_ty;
+        let try_name = format_ident!("__spaad_try_{}", wrapper_name);
+        let inputs = api_inputs.iter().skip(1);
+
+        Some(quote! {
+            #[allow(non_local_definitions)]
+            #[automatically_derived]
+            impl#impl_generics #self_ty #where_clause {
+                #[doc(hidden)]
+                #[allow(unused_mut)]
+                pub fn #try_name(
+                    &self,
+                    #(#inputs),*
+                ) -> impl ::std::future::Future<Output = #fallible_output> {
+                    let fut = self.addr.send(#msg);
+                    async move { #fallible_response }
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let send = send_message(do_send, msg, &handle_result);
+
+    quote! {
+        #[allow(unused_mut)]
+        #[track_caller]
+        #allow_deprecated
+        #(#attrs)* #vis fn #wrapper_name#fn_impl_generics(
+            #(#fn_decl_inputs),*
+        ) #ret
+            #fn_where
+        {
+            use ::spaad::export::xtra::prelude::*;
+
+            #(#assert_sendable)*
+
+            #msg_defs
+
+            #handler
+
+            #channel_conversion
+
+            #handles
+
+            #api_send
+
+            #mock
+
+            #conversions
+            #send
+        }
+
+        #broadcast
+
+        #stream
+
+        #batch
+
+        #sender
+    }
+}
+
+/// The wrapper's copy of an associated const, which is the actor's own. Its value is taken from
+/// the actor rather than evaluated again, since `Self` in it names the actor, whose other items
+/// (such as those in `inner_only` impl blocks) the wrapper might not have. A const whose type
+/// mentions `Self` is the actor's alone.
+pub fn transform_const(actor: &Type, item: &ImplItemConst) -> TokenStream2 {
+    let ImplItemConst {
+        attrs,
+        vis,
+        ident,
+        ty,
+        ..
+    } = item;
+    if find_self(quote!(#ty)).is_some() {
+        return quote!();
+    }
+
+    quote! {
+        #(#attrs)*
+        #vis const #ident: #ty = <#actor>::#ident;
+    }
+}
+
+/// Rewrites the arguments of a handler's signature into those of its message and wrappers.
+/// Arguments which are destructured (or ignored with `_`) are given a name of their own, since the
+/// actor's method still destructures them when called. With `arc_args`, borrowed arguments are
+/// sent as an `Arc` of what they borrow, and the names of those arguments are returned so that the
+/// handler can be given a reference into them. Arguments are kept along with their attributes.
+fn prepare_message_inputs(sig: &mut Signature, arc_args: bool) -> Vec<Ident> {
+    for (idx, arg) in sig.inputs.iter_mut().enumerate().skip(1) {
+        if let FnArg::Typed(pat_type) = arg {
+            let conditional = pat_type
+                .attrs
+                .iter()
+                .find(|attr| attr.path.is_ident("cfg") || attr.path.is_ident("cfg_attr"));
+            if let Some(attr) = conditional {
+                abort!(
+                    attr,
+                    "the arguments of handlers cannot be conditional";
+                    help = "each argument is a field of the message, which is always sent with \
+                            all of them; put the `cfg` on the handler instead"
+                );
+            }
+
+            let is_simple = matches!(
+                &*pat_type.pat,
+                Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none()
+            );
+            if !is_simple {
+                let ident = format_ident!("__spaad_arg{}", idx);
+                pat_type.pat = parse_quote!(#ident);
+            }
+        }
+    }
+
+    let mut arced = Vec::new();
+    if !arc_args {
+        return arced;
+    }
+
+    for arg in sig.inputs.iter_mut().skip(1) {
+        if let FnArg::Typed(PatType { pat, ty, .. }) = arg {
+            let ty_ref = match &**ty {
+                Type::Reference(ty_ref) => ty_ref,
+                _ => continue,
+            };
+            let is_static = ty_ref
+                .lifetime
+                .as_ref()
+                .map(|lif| lif.ident == "static")
+                .unwrap_or(false);
+            if is_static || ty_is_name(&ty_ref.elem, "Context") {
+                continue;
+            }
+            if let Some(mutability) = &ty_ref.mutability {
+                abort!(
+                    mutability,
+                    "`arc_args` can only share arguments which are borrowed immutably";
+                    help = "the handler cannot mutate data which the caller still shares"
+                );
+            }
+
+            let elem = ty_ref.elem.clone();
+            *ty = parse_quote!(::std::sync::Arc<#elem>);
+            if let Pat::Ident(pat) = &**pat {
+                arced.push(pat.ident.clone());
+            }
+        }
+    }
+
+    arced
+}
+
+/// Emits the expression sending `msg` to the actor, either awaiting its result through `send` or
+/// firing it off through `do_send`.
+fn send_message(do_send: bool, msg: TokenStream2, handle_result: &TokenStream2) -> TokenStream2 {
+    if do_send {
+        quote!(self.addr.do_send(#msg).expect("actor disconnected"))
+    } else {
+        quote! {
+            let __spaad_caller = ::std::panic::Location::caller();
+            let f = self.addr.send(#msg);
+            async move { f.await#handle_result }
+        }
+    }
+}
+
+/// Panics if the actor was disconnected. Since this happens when the future is polled, the caller
+/// of the `#[track_caller]` wrapper method is captured beforehand as `__spaad_caller` and named in
+/// the message, as the panic itself can't be located there.
+pub fn disconnected_panic() -> TokenStream2 {
+    quote! {
+        .unwrap_or_else(|_| {
+            ::std::panic!("actor disconnected (message sent at {})", __spaad_caller)
+        })
+    }
+}
+
+/// Whether a handler is part of the impl block's API trait: only public handlers can be, and
+/// only those without generics of their own, since the trait must be object safe.
+fn is_api_method(method: &ImplItemMethod) -> bool {
+    matches!(method.vis, Visibility::Public(_)) && method.sig.generics.params.is_empty()
+}
+
+/// What a handler's method in the API trait and field in the senders struct are made from.
+struct ApiHandler {
+    /// The name of the wrapper method.
+    name: Ident,
+    /// The name of the wrapper's hidden method sending the message, which they both call.
+    try_name: Ident,
+    /// The output of the hidden method, which reports disconnection rather than panicking.
+    output: TokenStream2,
+    cfgs: Vec<Attribute>,
+    /// The names and types of the arguments of the message.
+    args: Vec<(Ident, Box<Type>)>,
+}
+
+/// The parts of a handler shared by its API trait method and sender. This is `None` if the method
+/// is not a handler, or if it is split in two by a `cfg_attr`, as each copy would clash.
+fn api_handler(method: &ImplItemMethod, catch_panics: bool) -> Option<ApiHandler> {
+    let attrs = parse_attributes(&method.attrs);
+    let handler_attrs = attrs.get("handler")?;
+    if split_cfg_attr(method).is_some() {
+        return None;
+    }
+
+    let mut sig = method.sig.clone();
+    let arc_args = handler_attrs
+        .as_ref()
+        .map(|attrs| attrs.contains_key("arc_args"))
+        .unwrap_or(false);
+    prepare_message_inputs(&mut sig, arc_args);
+    let name = get_handler_name(&sig, handler_attrs);
+    let try_name = format_ident!("__spaad_try_{}", name);
+    let output = match deferred_output(&mut sig, handler_attrs) {
+        Some(ty) => fallible_output(&parse_quote!(-> #ty), false).0,
+        None => fallible_output(&sig.output, catch_panics).0,
+    };
+    let cfgs = method
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .cloned()
+        .collect();
+    // Patterns such as `mut` are not allowed in trait method declarations
+    let args = api_args(&sig);
+
+    Some(ApiHandler {
+        name,
+        try_name,
+        output,
+        cfgs,
+        args,
+    })
+}
+
+/// The declaration of a handler in the impl block's API trait, and its implementation for the
+/// wrapper. This is `None` if the method is not part of the trait.
+pub fn api_method(
+    args: &EntangleArgs,
+    method: &ImplItemMethod,
+) -> Option<(TokenStream2, TokenStream2)> {
+    if !is_api_method(method) {
+        return None;
+    }
+    let native_async = args.native_async.is_some();
+    let ApiHandler {
+        name,
+        try_name,
+        output,
+        cfgs,
+        args,
+    } = api_handler(method, args.catch_panics.is_some())?;
+    let forwarded = method
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("deprecated") || is_doc_hidden(attr));
+    // The try-helper is nested in the deprecated wrapper method, so it is deprecated too
+    let allow_deprecated = is_deprecated(&method.attrs).then(|| quote!(#[allow(deprecated)]));
+
+    let arg_names: Vec<_> = args.iter().map(|(name, _)| name).collect();
+    let inputs: Vec<_> = args.iter().map(|(name, ty)| quote!(#name: #ty)).collect();
+
+    // Native async methods return the wrapper's future as it is, rather than boxing it
+    if native_async {
+        let future = quote!(impl ::std::future::Future<Output = #output> + Send);
+        let decl = quote! {
+            #(#cfgs)*
+            #(#forwarded)*
+            fn #name(&self, #(#inputs),*) -> #future;
+        };
+        let implementation = quote! {
+            #(#cfgs)*
+            #allow_deprecated
+            fn #name(&self, #(#inputs),*) -> #future {
+                self.#try_name(#(#arg_names),*)
+            }
+        };
+
+        return Some((decl, implementation));
+    }
+
+    let decl = quote! {
+        #(#cfgs)*
+        #(#forwarded)*
+        async fn #name(&self, #(#inputs),*) -> #output;
+    };
+    let implementation = quote! {
+        #(#cfgs)*
+        #allow_deprecated
+        async fn #name(&self, #(#inputs),*) -> #output {
+            self.#try_name(#(#arg_names),*).await
+        }
+    };
+
+    Some((decl, implementation))
+}
+
+/// Whether a handler has a field in the impl block's senders struct: any handler can, as long as
+/// it has no generics of its own, since the field's closure can't be generic.
+fn is_sender_method(method: &ImplItemMethod) -> bool {
+    method.sig.generics.params.is_empty()
+}
+
+/// The field for a handler in the impl block's senders struct, and its initializer, which is a
+/// closure sending the message through a clone of the wrapper. The field has the handler's
+/// visibility, which is also returned. This is `None` if the method has no sender.
+pub fn sender_field(
+    method: &ImplItemMethod,
+    catch_panics: bool,
+) -> Option<(Visibility, TokenStream2, TokenStream2)> {
+    if !is_sender_method(method) {
+        return None;
+    }
+    let ApiHandler {
+        name,
+        try_name,
+        output,
+        cfgs,
+        args,
+    } = api_handler(method, catch_panics)?;
+    let vis = &method.vis;
+    let hidden = method.attrs.iter().filter(|attr| is_doc_hidden(attr));
+    let allow_deprecated = is_deprecated(&method.attrs).then(|| quote!(#[allow(deprecated)]));
+
+    let arg_names: Vec<_> = args.iter().map(|(name, _)| name).collect();
+    let arg_tys = args.iter().map(|(_, ty)| ty);
+
+    let field = quote! {
+        #(#cfgs)*
+        #(#hidden)*
+        #vis #name: ::std::boxed::Box<
+            dyn Fn(#(#arg_tys),*) -> ::std::pin::Pin<
+                ::std::boxed::Box<dyn ::std::future::Future<Output = #output> + Send>
+            > + Send + Sync
+        >,
+    };
+    let init = quote! {
+        #(#cfgs)*
+        #name: {
+            let wrapper = self.clone();
+            ::std::boxed::Box::new(move |#(#arg_names),*| {
+                let wrapper = wrapper.clone();
+                ::std::boxed::Box::pin(async move {
+                    #allow_deprecated
+                    wrapper.#try_name(#(#arg_names),*).await
+                })
+            })
+        },
+    };
+
+    Some((vis.clone(), field, init))
+}
+
+/// The names and types of the arguments of an API method, which are those of the message.
+fn api_args(sig: &Signature) -> Vec<(Ident, Box<Type>)> {
+    sig.inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(PatType { pat, ty, .. }) => match (&**pat, &**ty) {
+                (_, Type::Reference(ty_ref)) if is_context_ref(ty_ref) => None,
+                (_, ty) if reply_ty(ty).is_some() => None,
+                (Pat::Ident(pat), _) => Some((pat.ident.clone(), ty.clone())),
+                _ => unreachable!("patterns are replaced with names"),
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+/// The index, name and response type of a handler's `Reply<T>` argument, if it has one.
+fn reply_arg(sig: &Signature) -> Option<(usize, Ident, Type)> {
+    let mut replies = sig
+        .inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, arg)| match arg {
+            FnArg::Typed(PatType { pat, ty, .. }) => match (&**pat, reply_ty(ty)) {
+                (Pat::Ident(pat), Some(response)) => Some((idx, pat.ident.clone(), response)),
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        });
+    let reply = replies.next();
+    if let Some((idx, _, _)) = replies.next() {
+        abort!(
+            sig.inputs[idx],
+            "handlers can only take one `Reply`";
+            help = "a message has only one response"
+        );
+    }
+
+    reply
+}
+
+/// The type of the response sent through an argument of type `Reply<T>`. Like `Context`, the type
+/// is recognised by its name.
+fn reply_ty(ty: &Type) -> Option<Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == "Reply" && args.args.len() == 1 => {
+            match &args.args[0] {
+                GenericArgument::Type(ty) => Some(ty.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// With `defer`, rewrites the return type of a handler returning `impl Future<Output = T>` into
+/// the `spaad::Deferred<T>` its message responds with, returning `T`. A handler taking a
+/// `Reply<T>` responds with one too, which is the receiving half of the reply.
+fn deferred_output(
+    sig: &mut Signature,
+    handler_attrs: &Option<HashMap<String, (Ident, Lit)>>,
+) -> Option<Type> {
+    if let Some((_, _, response)) = reply_arg(sig) {
+        if let Some(defer) = handler_attrs.as_ref().and_then(|attrs| attrs.get("defer")) {
+            abort!(
+                defer.0,
+                "handlers taking a `Reply` cannot also have `defer`";
+                help = "the response is sent through the `Reply`, so it is already deferred"
+            );
+        }
+        if let ReturnType::Type(_, ty) = &sig.output {
+            abort!(
+                ty,
+                "handlers taking a `Reply` cannot return anything";
+                help = "send the response through the `Reply` instead"
+            );
+        }
+
+        sig.output = parse_quote!(-> ::spaad::Deferred<#response>);
+        return Some(response);
+    }
+
+    handler_attrs.as_ref()?.get("defer")?;
+
+    if let Some(asyncness) = &sig.asyncness {
+        abort!(
+            asyncness,
+            "handlers with `defer` cannot be `async`";
+            help = "they return the future of their response, which must not borrow the actor, \
+                    so that the actor can handle other messages while it is awaited"
+        );
+    }
+
+    let bounds = match &sig.output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::ImplTrait(ty) => Some(&ty.bounds),
+            _ => None,
+        },
+        ReturnType::Default => None,
+    };
+    let output = bounds
+        .into_iter()
+        .flatten()
+        .filter_map(|bound| match bound {
+            TypeParamBound::Trait(bound) => bound.path.segments.last(),
+            TypeParamBound::Lifetime(_) => None,
+        })
+        .filter(|segment| segment.ident == "Future")
+        .filter_map(|segment| match &segment.arguments {
+            PathArguments::AngleBracketed(args) => Some(&args.args),
+            _ => None,
+        })
+        .flatten()
+        .find_map(|arg| match arg {
+            GenericArgument::Binding(binding) if binding.ident == "Output" => {
+                Some(binding.ty.clone())
+            }
+            _ => None,
+        });
+    let output = match output {
+        Some(output) => output,
+        None => abort!(
+            sig.output,
+            "handlers with `defer` must return `impl Future<Output = T> + Send + 'static`";
+            help = "the future is awaited after the handler has returned, so it must not borrow \
+                    the actor or the message"
+        ),
+    };
+
+    sig.output = parse_quote!(-> ::spaad::Deferred<#output>);
+    Some(output)
+}
+
+fn is_deprecated(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident("deprecated"))
+}
+
+fn is_doc_hidden(attr: &Attribute) -> bool {
+    match attr.parse_meta() {
+        Ok(Meta::List(list)) if list.path.is_ident("doc") => list.nested.iter().any(|nested| {
+            matches!(nested, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("hidden"))
+        }),
+        _ => false,
+    }
+}
+
+/// What a send which reports disconnection rather than panicking resolves to, and how to get there
+/// from the result of `send`. Handlers which catch panics report both as a `spaad::HandlerError`.
+fn fallible_output(
+    output: &ReturnType,
+    catch_panics: bool,
+) -> (TokenStream2, Option<TokenStream2>) {
+    if catch_panics {
+        let ty = match output {
+            ReturnType::Default => quote!(()),
+            ReturnType::Type(_, ty) => quote!(#ty),
+        };
+        return (
+            quote!(::std::result::Result<#ty, ::spaad::HandlerError>),
+            Some(quote!(.map_err(::spaad::HandlerError::from).and_then(|x| x))),
+        );
+    }
+
+    match transform_ret(output) {
+        Some(ret) => (ret, Some(quote!(.and_then(|x| x)))),
+        None => {
+            let ty = match output {
+                ReturnType::Default => quote!(()),
+                ReturnType::Type(_, ty) => quote!(#ty),
+            };
+            (
+                quote!(::std::result::Result<#ty, ::spaad::export::xtra::Disconnected>),
+                None,
+            )
+        }
+    }
+}
+
+/// The self type of the impl block, with the wrapper's name changed (e.g to `XWeak<T>`).
+fn rename_self_ty(impl_block: &ItemImpl, rename: impl Fn(&Ident) -> Ident) -> Type {
+    let mut ty = (*impl_block.self_ty).clone();
+    if let Type::Path(path) = &mut ty {
+        let last = path.path.segments.last_mut().unwrap();
+        last.ident = rename(&last.ident);
+    }
+    ty
+}
+
+/// Finds the first reference or lifetime in a type which isn't `'static`, if there is one.
+fn find_borrow(ty: &Type) -> Option<TokenStream2> {
+    let is_static = |lifetime: &Lifetime| lifetime.ident == "static";
+    match ty {
+        Type::Reference(reference) => match &reference.lifetime {
+            Some(lifetime) if is_static(lifetime) => find_borrow(&reference.elem),
+            _ => Some(quote!(#reference)),
+        },
+        Type::Path(path) => path
+            .path
+            .segments
+            .iter()
+            .filter_map(|segment| match &segment.arguments {
+                PathArguments::AngleBracketed(args) => Some(&args.args),
+                _ => None,
+            })
+            .flatten()
+            .find_map(|arg| match arg {
+                GenericArgument::Lifetime(lifetime) if !is_static(lifetime) => {
+                    Some(quote!(#lifetime))
+                }
+                GenericArgument::Type(ty) => find_borrow(ty),
+                _ => None,
+            }),
+        Type::TraitObject(object) => object.bounds.iter().find_map(|bound| match bound {
+            TypeParamBound::Lifetime(lifetime) if !is_static(lifetime) => Some(quote!(#lifetime)),
+            _ => None,
+        }),
+        Type::Array(array) => find_borrow(&array.elem),
+        Type::Slice(slice) => find_borrow(&slice.elem),
+        Type::Tuple(tuple) => tuple.elems.iter().find_map(find_borrow),
+        Type::Paren(paren) => find_borrow(&paren.elem),
+        Type::Group(group) => find_borrow(&group.elem),
+        _ => None,
+    }
+}
+
+/// Whether the identifier appears anywhere in the tokens, such as a type parameter in a type.
+pub fn mentions(tokens: TokenStream2, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(token) => token == *ident,
+        proc_macro2::TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// Finds the first use of `Self` in some tokens, if there is one.
+fn find_self(tokens: TokenStream2) -> Option<Ident> {
+    tokens.into_iter().find_map(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) if ident == "Self" => Some(ident),
+        proc_macro2::TokenTree::Group(group) => find_self(group.stream()),
+        _ => None,
+    })
+}
+
+fn returns_unit(r: &ReturnType) -> bool {
+    match r {
+        ReturnType::Default => true,
+        ReturnType::Type(_, ty) => matches!(&**ty, Type::Tuple(tuple) if tuple.elems.is_empty()),
+    }
+}
+
+pub fn transform_ret(r: &ReturnType) -> Option<proc_macro2::TokenStream> {
+    if let ReturnType::Type(_, ret_ty) = r {
+        if ty_is_name(ret_ty, "Result") {
+            if let Type::Path(ty_path) = ret_ty.deref() {
+                let arg = &ty_path.path.segments.last().unwrap().arguments;
+                if let PathArguments::AngleBracketed(generics) = arg {
+                    let last = generics.args.last().unwrap();
+                    if let GenericArgument::Type(ty) = last {
+                        if ty_is_name(ty, "Disconnected") {
+                            return Some(quote!(#ret_ty));
                         }
                     }
                 }
@@ -386,6 +1856,29 @@ fn transform_ret(r: &ReturnType) -> Option<proc_macro2::TokenStream> {
     None
 }
 
+/// The generic arguments of the actor an impl block is for, as written in its self type. An impl
+/// block such as `impl Cache<u32>` is for only one instantiation of the actor, and the parameters
+/// of a generic one may be declared in a different order to the actor's, so its own generics
+/// can't be used in their place.
+fn actor_generic_args(impl_block: &ItemImpl) -> PathArguments {
+    match &*impl_block.self_ty {
+        Type::Path(path) => path.path.segments.last().unwrap().arguments.clone(),
+        _ => PathArguments::None,
+    }
+}
+
+/// Generic arguments in the form they are given to a path in an expression, i.e `::<T>`.
+fn turbofish(args: &PathArguments) -> TokenStream2 {
+    match args {
+        PathArguments::AngleBracketed(args) => {
+            let mut args = args.clone();
+            args.colon2_token = Some(Default::default());
+            quote!(#args)
+        }
+        _ => quote!(),
+    }
+}
+
 fn transform_static_methods(
     name: &Ident,
     actor_name: proc_macro2::TokenStream,
@@ -393,7 +1886,7 @@ fn transform_static_methods(
     has_spawn: bool,
     method: ImplItemMethod,
     attrs: &AttrMap,
-    impl_ty_generics: TypeGenerics,
+    act_ty_generics: &PathArguments,
 ) -> proc_macro2::TokenStream {
     let sig = &method.sig;
     let arg_inputs = &sig.inputs;
@@ -407,13 +1900,13 @@ fn transform_static_methods(
         .map(|x| &**x)
         .collect();
 
-    if has_create || has_spawn {
+    if has_create || has_spawn || attrs.contains_key("reattach") {
         transform_constructors(
             name,
             actor_name,
             method.clone(),
             attrs,
-            impl_ty_generics,
+            act_ty_generics,
             arg_inputs,
             inputs,
         )
@@ -421,7 +1914,7 @@ fn transform_static_methods(
         let fn_name = &sig.ident;
         let method_ty_generics = sig.generics.split_for_impl().1;
         let method_turbo = method_ty_generics.as_turbofish();
-        let act_turbo = impl_ty_generics.as_turbofish();
+        let act_turbo = turbofish(act_ty_generics);
         let dot_await = if sig.asyncness.is_some() {
             Some(quote!(.await))
         } else {
@@ -442,7 +1935,7 @@ fn transform_constructors(
     actor_name: proc_macro2::TokenStream,
     method: ImplItemMethod,
     attrs: &AttrMap,
-    act_ty_generics: TypeGenerics,
+    act_ty_generics: &PathArguments,
     arg_inputs: &Punctuated<FnArg, Token![,]>,
     inputs: Vec<&Pat>,
 ) -> proc_macro2::TokenStream {
@@ -452,7 +1945,48 @@ fn transform_constructors(
         sig,
         ..
     } = method;
-    let is_name = |ty, name| ty_is_name(ty, name);
+    use self::ty_is_name as is_name;
+
+    // The actor's own address is passed to the constructor rather than taken by the generated ones
+    let is_address_arg = |arg: &FnArg| is_address_arg(arg, name);
+    let mut address_args = arg_inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, arg)| is_address_arg(arg));
+    let address_arg = address_args.next().map(|(idx, _)| idx);
+    if let Some((_, arg)) = address_args.next() {
+        abort!(
+            arg,
+            "constructors can only take the actor's own address once";
+            help = "clone the address inside of the constructor instead"
+        );
+    }
+    let test_inputs: Punctuated<FnArg, Token![,]> = arg_inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Typed(pat_ty) if is_address_arg(arg) => {
+                let pat = &pat_ty.pat;
+                parse_quote!(#pat: ::spaad::export::xtra::Address<#actor_name#act_ty_generics>)
+            }
+            arg => arg.clone(),
+        })
+        .collect();
+    let arg_inputs: Punctuated<FnArg, Token![,]> = arg_inputs
+        .iter()
+        .filter(|arg| !is_address_arg(arg))
+        .cloned()
+        .collect();
+    let arg_inputs = &arg_inputs;
+    let call_inputs = |addr: TokenStream2| -> Vec<TokenStream2> {
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(i, pat)| match address_arg {
+                Some(idx) if idx == i => addr.clone(),
+                _ => quote!(#pat),
+            })
+            .collect()
+    };
 
     if matches!(
         &sig.output,
@@ -467,7 +2001,8 @@ fn transform_constructors(
     let mut spawn: Option<TokenStream> = None;
 
     if let Some(attr) = attrs.get("spawn") {
-        let spawner = attr.as_ref().and_then(|attr| get_spawner(attr));
+        let spawner = attr.as_ref().and_then(get_spawner);
+        let message_cap = get_message_cap(attr);
 
         let mut new_generics = sig.generics.clone();
 
@@ -477,8 +2012,8 @@ fn transform_constructors(
             new_generics.params.push(param);
         }
 
-        let (impl_generics, _ty_generics, where_clause) = new_generics.split_for_impl();
-        let act_turbo = act_ty_generics.as_turbofish();
+        let (new_impl_generics, _ty_generics, new_where_clause) = new_generics.split_for_impl();
+        let act_turbo = turbofish(act_ty_generics);
         let (_, old_ty_generics, _) = sig.generics.split_for_impl();
         let fn_turbo = old_ty_generics.as_turbofish();
         let act_fn_name = &sig.ident;
@@ -503,27 +2038,81 @@ fn transform_constructors(
             quote!(actor_spawner)
         };
 
+        // With its address as an argument, the actor is constructed only once its context exists
+        let body = if address_arg.is_some() {
+            let inputs = call_inputs(quote!(::std::clone::Clone::clone(&addr)));
+            quote! {
+                let (addr, ctx) = ::spaad::export::xtra::Context::new(#message_cap);
+                let act = #actor_name#act_turbo::#act_fn_name#fn_turbo(#(#inputs),*);
+                ::spaad::export::xtra::spawn::Spawner::spawn(#spawner_ref, ctx.run(act));
+            }
+        } else {
+            quote! {
+                let act = #actor_name#act_turbo::#act_fn_name#fn_turbo(#(#inputs),*);
+                let addr = act.create(#message_cap).spawn(#spawner_ref);
+            }
+        };
+
+        // The same constructor, spawning the actor on the runtime of a given tokio handle
+        let handle_fn_name = format_ident!("{}_on_handle", fn_name);
+        let (impl_generics, _, where_clause) = sig.generics.split_for_impl();
+        let handle_body = if address_arg.is_some() {
+            let inputs = call_inputs(quote!(::std::clone::Clone::clone(&addr)));
+            quote! {
+                let (addr, ctx) = ::spaad::export::xtra::Context::new(#message_cap);
+                let act = #actor_name#act_turbo::#act_fn_name#fn_turbo(#(#inputs),*);
+                ::std::mem::drop(handle.spawn(ctx.run(act)));
+            }
+        } else {
+            quote! {
+                let act = #actor_name#act_turbo::#act_fn_name#fn_turbo(#(#inputs),*);
+                let (addr, run) = act.create(#message_cap).run();
+                ::std::mem::drop(handle.spawn(run));
+            }
+        };
+
         spawn = Some(quote! {
-            #(#method_attrs)* #vis fn #fn_name#impl_generics(
+            #[allow(clippy::new_without_default)]
+            #(#method_attrs)* #vis fn #fn_name#new_impl_generics(
                 #arg_inputs
                 #spawner_arg
-            ) -> Self #where_clause {
+            ) -> Self #new_where_clause {
                 use ::spaad::export::xtra::prelude::*;
-                let act = #actor_name#act_turbo::#act_fn_name#fn_turbo(#(#inputs),*);
-                let addr = act.create(::std::option::Option::None).spawn(#spawner_ref);
-                #name { addr }
+                #body
+                ::spaad::export::Spawned::spawned(addr)
+            }
+
+            ::spaad::__on_tokio_handle! {
+                #(#method_attrs)* #vis fn #handle_fn_name#impl_generics(
+                    handle: &::spaad::export::tokio::runtime::Handle,
+                    #arg_inputs
+                ) -> Self #where_clause {
+                    use ::spaad::export::xtra::prelude::*;
+                    #handle_body
+                    ::spaad::export::Spawned::spawned(addr)
+                }
             }
         });
     };
 
     let mut create = None;
     if let Some(attr) = attrs.get("create") {
+        if let Some(idx) = address_arg {
+            abort!(
+                inputs[idx],
+                "constructors marked with `create` cannot take the actor's address";
+                help = "xtra only gives the address along with the `ActorManager`, once the actor \
+                    is constructed. Use `spawn`, or take it in `Actor::started` with `ctx.address()`"
+            );
+        }
+
         let (impl_generics, ty_generics, where_clause) = sig.generics.split_for_impl();
-        let act_turbo = act_ty_generics.as_turbofish();
+        let act_turbo = turbofish(act_ty_generics);
         let fn_turbo = ty_generics.as_turbofish();
         let act_fn_name = &sig.ident;
 
         let fn_name = get_ctor_name(&sig, attr, false);
+        let message_cap = get_message_cap(attr);
         create = Some(quote! {
             #(#method_attrs)* #vis fn #fn_name#impl_generics(
                 #arg_inputs
@@ -532,13 +2121,86 @@ fn transform_constructors(
             {
                 use ::spaad::export::xtra::prelude::*;
                 let act = #actor_name#act_turbo::#act_fn_name#fn_turbo(#(#inputs),*);
-                let mgr = act.create(::std::option::Option::None);
+                let mgr = act.create(#message_cap);
                 mgr
             }
         })
     };
 
-    return quote!(#spawn #create);
+    let mut reattach = None;
+    if let Some(attr) = attrs.get("reattach") {
+        let (impl_generics, ty_generics, where_clause) = sig.generics.split_for_impl();
+        let act_turbo = turbofish(act_ty_generics);
+        let fn_turbo = ty_generics.as_turbofish();
+        let act_fn_name = &sig.ident;
+
+        let fn_name = get_reattach_name(&sig, attr);
+        let disconnected = disconnected_panic();
+        let inputs = call_inputs(quote!(::std::clone::Clone::clone(&self.addr)));
+        reattach = Some(quote! {
+            #[track_caller]
+            #(#method_attrs)* #vis fn #fn_name#impl_generics(
+                &self,
+                #arg_inputs
+            ) -> impl ::std::future::Future<Output = ()>
+                #where_clause
+            {
+                use ::spaad::export::xtra::prelude::*;
+                let __spaad_caller = ::std::panic::Location::caller();
+                let act = #actor_name#act_turbo::#act_fn_name#fn_turbo(#(#inputs),*);
+                let fut = self.addr.send(::spaad::export::Reattach(act));
+                async move { fut.await#disconnected }
+            }
+        })
+    };
+
+    // Lets unit tests construct the actor itself and call its methods directly
+    let (impl_generics, ty_generics, where_clause) = sig.generics.split_for_impl();
+    let act_turbo = turbofish(act_ty_generics);
+    let fn_turbo = ty_generics.as_turbofish();
+    let act_fn_name = &sig.ident;
+    let test_fn_name = format_ident!("__{}_actor", act_fn_name);
+    let cfgs = method_attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+    let test_ctor = quote! {
+        #(#cfgs)*
+        #[cfg(test)]
+        #[allow(dead_code)]
+        #vis fn #test_fn_name#impl_generics(
+            #test_inputs
+        ) -> #actor_name#act_ty_generics
+            #where_clause
+        {
+            #actor_name#act_turbo::#act_fn_name#fn_turbo(#(#inputs),*)
+        }
+    };
+
+    quote!(#spawn #create #reattach #test_ctor)
+}
+
+/// Whether an argument of a constructor is the actor's own address, written as `Address<Self>` or
+/// as an `Address` of the actor by name. The address of any other actor is an argument like any
+/// other.
+fn is_address_arg(arg: &FnArg, name: &Ident) -> bool {
+    let ty = match arg {
+        FnArg::Typed(PatType { ty, .. }) => ty,
+        FnArg::Receiver(_) => return false,
+    };
+    let segment = match &**ty {
+        Type::Path(TypePath { qself: None, path }) => path.segments.last(),
+        _ => None,
+    };
+    let args = match segment {
+        Some(segment) if segment.ident == "Address" => match &segment.arguments {
+            PathArguments::AngleBracketed(args) if args.args.len() == 1 => &args.args[0],
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    match args {
+        GenericArgument::Type(ty) => ty_is_name(ty, "Self") || ty_is_name(ty, &name.to_string()),
+        _ => false,
+    }
 }
 
 fn get_ctor_name(
@@ -556,29 +2218,230 @@ fn get_ctor_name(
     for (key, (ident, lit)) in attr_arg_map {
         match &**key {
             "rename" => match lit {
-                Lit::Str(lit) => return format_ident!("{}", lit.value()),
+                Lit::Str(lit) => return parse_rename(lit),
                 _ => abort!(lit, "Expected rename target to be a string"),
             },
             "spawner" if spawn => {}
+            "message_cap" => {}
             _ if spawn => abort!(
                 ident,
-                "Only two valid arguments here: `rename` and `spawner`"
+                "Only three valid arguments here: `rename`, `spawner` and `message_cap`"
+            ),
+            _ => abort!(
+                ident,
+                "Only two valid arguments here: `rename` and `message_cap`"
             ),
-            _ => abort!(ident, "Only one valid argument here: `rename`"),
         }
     }
 
     fn_name
 }
 
+/// `reattach` methods are called `reattach` unless renamed, since they take `&self` and so can't
+/// usefully share the constructor's name.
+fn get_reattach_name(sig: &Signature, attr: &Option<HashMap<String, (Ident, Lit)>>) -> Ident {
+    let renamed = attr
+        .as_ref()
+        .map(|attr| attr.contains_key("rename"))
+        .unwrap_or(false);
+
+    // Also checks that no other arguments were given
+    let name = get_ctor_name(sig, attr, false);
+
+    if renamed {
+        name
+    } else {
+        format_ident!("reattach")
+    }
+}
+
+/// The name of the wrapper method for a handler, which can differ from the actor's method with
+/// `#[spaad::handler(rename = "...")]`.
+fn get_handler_name(sig: &Signature, attr: &Option<HashMap<String, (Ident, Lit)>>) -> Ident {
+    match attr.as_ref().and_then(|attr| attr.get("rename")) {
+        Some((_, Lit::Str(lit))) => parse_rename(lit),
+        Some((_, lit)) => abort!(lit, "Expected rename target to be a string"),
+        None => sig.ident.clone(),
+    }
+}
+
+/// The method which decides whether a message is handled, from
+/// `#[spaad::handler(guard = "...")]`.
+fn get_guard(attr: &Option<HashMap<String, (Ident, Lit)>>) -> Option<Ident> {
+    match attr.as_ref().and_then(|attr| attr.get("guard")) {
+        Some((_, Lit::Str(lit))) => Some(parse_rename(lit)),
+        Some((_, lit)) => abort!(lit, "Expected guard to be a method name string"),
+        None => None,
+    }
+}
+
+/// The field holding the `spaad::Coalesce` of a coalescing handler, and the argument keying it.
+fn get_coalesce(attr: &Option<HashMap<String, (Ident, Lit)>>) -> Option<(Ident, Option<Ident>)> {
+    let attr = attr.as_ref()?;
+    let key = match attr.get("coalesce_key") {
+        Some((_, Lit::Str(lit))) => Some(parse_rename(lit)),
+        Some((_, lit)) => abort!(lit, "Expected coalesce_key to be an argument name string"),
+        None => None,
+    };
+    let field = match attr.get("coalesce") {
+        Some((_, Lit::Str(lit))) => parse_rename(lit),
+        Some((_, lit)) => abort!(lit, "Expected coalesce to be a field name string"),
+        None => match attr.get("coalesce_key") {
+            Some((flag, _)) => abort!(
+                flag,
+                "`coalesce_key` must be used along with `coalesce`";
+                help = "name the field of type `spaad::Coalesce<K>` keeping track of the \
+                        handler with `coalesce = \"...\"`"
+            ),
+            None => return None,
+        },
+    };
+    Some((field, key))
+}
+
+fn parse_rename(lit: &LitStr) -> Ident {
+    match syn::parse_str::<Ident>(&lit.value()) {
+        Ok(ident) => Ident::new(&ident.to_string(), lit.span()),
+        Err(_) => abort!(lit, "`{}` is not a valid identifier", lit.value()),
+    }
+}
+
+/// The entries for a method in the impl block's list of handlers, if it is one: the name of its
+/// wrapper method, gated on the method's `cfg`s. A method with spaad attributes in a `cfg_attr`
+/// is split into its two copies first, as it is when the handlers are generated.
+pub fn handler_list_entries(method: &ImplItemMethod) -> Vec<TokenStream2> {
+    if let Some([with, without]) = split_cfg_attr(method) {
+        let mut entries = handler_list_entries(&with);
+        entries.extend(handler_list_entries(&without));
+        return entries;
+    }
+
+    let attrs = parse_attributes(&method.attrs);
+    let handler = match attrs.get("handler") {
+        Some(handler) => handler,
+        None => return Vec::new(),
+    };
+    let name = get_handler_name(&method.sig, handler).to_string();
+    let cfgs = method.attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+
+    vec![quote!(#(#cfgs)* #name)]
+}
+
+/// The keys which `#[spaad::handler(...)]` takes. `background` is only there to be rejected with
+/// a more helpful error.
+const HANDLER_KEYS: &[&str] = &[
+    "send",
+    "broadcast",
+    "batch",
+    "stream",
+    "into",
+    "arc_args",
+    "guard",
+    "coalesce",
+    "coalesce_key",
+    "defer",
+    "msg",
+    "rename",
+    "background",
+];
+
+/// The methods generated for every wrapper, which no method of an impl block can be named.
+const WRAPPER_METHODS: &[&str] = &["address", "owned_address", "into_address", "ready"];
+
+/// Checks that no renamed wrapper method clashes with another method of the wrapper in the same
+/// impl block, and that none clashes with the methods every wrapper has. Clashes between other
+/// unrenamed methods are left for the compiler to report, since those can be legitimate (e.g. two
+/// copies of a method behind different `cfg`s).
+pub fn check_renames(impl_block: &ItemImpl) {
+    let mut names: HashMap<String, (Ident, bool)> = HashMap::new();
+
+    for item in &impl_block.items {
+        let method = match item {
+            ImplItem::Method(method) => method,
+            _ => continue,
+        };
+        let sig = &method.sig;
+        let attrs = parse_attributes(&method.attrs);
+
+        let wrapper_names = if let Some(handler) = attrs.get("handler") {
+            let renamed = handler
+                .as_ref()
+                .map(|attr| attr.contains_key("rename"))
+                .unwrap_or(false);
+            vec![(get_handler_name(sig, handler), renamed)]
+        } else if matches!(sig.inputs.first(), Some(arg @ FnArg::Typed(_)) if !is_typed_self(arg))
+            || sig.inputs.is_empty()
+        {
+            let ctors: Vec<_> = ["spawn", "create", "reattach"]
+                .iter()
+                .filter_map(|ctor| {
+                    let attr = attrs.get(*ctor)?;
+                    let renamed = attr
+                        .as_ref()
+                        .map(|attr| attr.contains_key("rename"))
+                        .unwrap_or(false);
+                    let name = match *ctor {
+                        "reattach" => get_reattach_name(sig, attr),
+                        ctor => get_ctor_name(sig, attr, ctor == "spawn"),
+                    };
+                    Some((name, renamed))
+                })
+                .collect();
+
+            if ctors.is_empty() {
+                vec![(sig.ident.clone(), false)]
+            } else {
+                ctors
+            }
+        } else {
+            // Non-handler methods taking `self` are only kept on the actor
+            continue;
+        };
+
+        for (name, renamed) in wrapper_names {
+            if WRAPPER_METHODS.iter().any(|method| name == method) {
+                abort!(
+                    name,
+                    "every wrapper already has a method named `{}`",
+                    name;
+                    help = "pass `rename = \"...\"` to the method's spaad attribute to give the \
+                            wrapper's method another name, or rename the method itself"
+                );
+            }
+            match names.get(&name.to_string()) {
+                Some((other, other_renamed)) if renamed || *other_renamed => {
+                    let renamed = if renamed { &name } else { other };
+                    abort!(
+                        renamed,
+                        "the wrapper already has a method named `{}` in this impl block",
+                        name
+                    );
+                }
+                _ => {
+                    names.insert(name.to_string(), (name, renamed));
+                }
+            }
+        }
+    }
+}
+
+/// The capacity of the actor's mailbox, which is unbounded unless `message_cap` is given.
+fn get_message_cap(attr: &Option<HashMap<String, (Ident, Lit)>>) -> TokenStream2 {
+    match attr.as_ref().and_then(|attr| attr.get("message_cap")) {
+        Some((_, Lit::Int(cap))) => quote!(::std::option::Option::Some(#cap)),
+        Some((_, cap)) => abort!(cap, "Expected the message cap to be an integer"),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
 fn get_spawner(attr: &HashMap<String, (Ident, Lit)>) -> Option<TokenStream2> {
     if let Some((_, spawner)) = attr.get("spawner") {
         let spawner = match spawner {
             Lit::Str(lit) => match &*lit.value().to_lowercase() {
-                "tokio" => quote!(::spaad::export::xtra::spawn::Tokio::Global),
-                "async_std" => quote!(::spaad::export::xtra::spawn::AsyncStd),
-                "smol" => quote!(::spaad::export::xtra::spawn::Smol::Global),
-                "wasm_bindgen" => quote!(::spaad::export::xtra::spawn::WasmBindgen),
+                "tokio" => quote_spanned!(lit.span()=> ::spaad::__spawner_tokio!()),
+                "async_std" => quote_spanned!(lit.span()=> ::spaad::__spawner_async_std!()),
+                "smol" => quote_spanned!(lit.span()=> ::spaad::__spawner_smol!()),
+                "wasm_bindgen" => quote_spanned!(lit.span()=> ::spaad::__spawner_wasm_bindgen!()),
                 _ => abort!(
                     spawner,
                     "Expected one of \"tokio\", \"async_std\", \"smol\", \"wasm_bindgen\""
@@ -7,9 +7,11 @@ use quote::quote;
 use syn::*;
 
 mod entangle;
+mod wrap;
 
 /// The main item of the crate. This is a proc macro used as an attribute on the actor struct
 /// definition, `Actor` implementation, and on an impl block in which the handler functions are used.
+/// It can also be used on a whole module of actors, as described under `entangle!`.
 ///
 /// ## Example
 /// ```rust,ignore
@@ -42,6 +44,53 @@ mod entangle;
 /// }
 /// ```
 ///
+/// ## The `Actor` implementation
+/// spaad does not generate an `Actor` implementation for the actor. It must be written under
+/// `#[spaad::entangled]` like any other trait impl, as above, so everything that xtra allows to be
+/// customised on `Actor` (such as `started` and `stopped`) is set there exactly as it would be
+/// without spaad.
+///
+/// Alongside it, a handler for a hidden message is generated which stops the actor. This is what
+/// `spaad::shutdown_all` sends, to stop a collection of actors and wait for them to handle it.
+///
+/// Messages sent to an actor before its `started` method has returned wait in its mailbox until
+/// then. `ready` on the wrapper sends it another hidden message, which it does nothing with, so it
+/// resolves once startup has finished, or straight away if it already had. That message's handler
+/// is generated with the wrapper, so `ready` works with an `Actor` impl written by hand too.
+/// `started` can't return an error, so startup fails by calling `ctx.stop()`, after which `ready`
+/// resolves to `Err(xtra::Disconnected)` (as it does if the actor has stopped since):
+///
+/// ```rust,ignore
+/// let loader = Loader::new(config);
+/// loader.ready().await?; // `Loader::started` has now finished loading
+/// ```
+///
+/// Like the methods for [accessing the address](#accessing-the-address), `ready` is on every
+/// wrapper, so a handler named `ready` has to give its wrapper method another name with `rename`.
+///
+/// The wrapper holds a strong `xtra::Address`, so by default xtra stops the actor once the last
+/// of its addresses is dropped, and weak handles don't keep it running. However, the last address
+/// tells the actor so before it is released itself, and the actor only stops if no strong address
+/// is left when it checks. An idle actor can check straight away and keep running with nothing to
+/// reach it. With `#[spaad::entangled(stop_on_last_drop)]` on the actor struct, the clones of a
+/// wrapper share a guard which sends the actor the hidden stop message once the last of them is
+/// dropped, after which its `stopping` and `stopped` methods are called as usual. Only the
+/// wrappers count for this: an address or message channel taken from a wrapper, such as with
+/// `into_address`, does not keep the actor running. Since a wrapper made from an address couldn't
+/// share the guard, the wrapper has no `From<xtra::Address>` implementation then, and is only
+/// made by its constructors and builder.
+///
+/// xtra gives no way to wait for the last strong address of an actor to be dropped, so wrappers
+/// have no `on_disconnect` method. To wait for an actor to stop instead, such as to clean up after
+/// it elsewhere, give it the sending half of a oneshot channel and send on it in `Actor::stopped`.
+/// The receiver resolves once the actor has stopped, or straight away if it already had, and
+/// along with `stop_on_last_drop` this is as soon as the last wrapper has been dropped.
+///
+/// `#[spaad::entangled(must_use)]` on the actor struct makes the wrapper `#[must_use]`, so that
+/// discarding one, such as by calling a constructor without binding its result, is warned about.
+/// This is most useful along with `stop_on_last_drop`, where that always stops the actor straight
+/// away.
+///
 /// ## Constructors
 /// To emit a constructor for an actor, the `#[spaad::spawn]` or `#[spaad::create]` attributes can
 /// be used. The `spawn` macro will emit a method that constructs the actor with the given arguments
@@ -60,6 +109,96 @@ mod entangle;
 ///
 /// This will cause a `create` function to be emitted, as well as a a spawn function named `new`.
 ///
+/// The actor's mailbox is unbounded by default. `message_cap = 16` on either attribute gives it a
+/// capacity instead, just like passing `Some(16)` to `Actor::create`. When the mailbox is full, the
+/// future of a wrapper method waits until there is room for the message, but a `fire_and_forget`
+/// method blocks the thread until then.
+///
+/// Besides its capacity, the mailbox can't be swapped out: xtra's `Context` always creates its own
+/// queue, so `mailbox = ...` is rejected rather than threaded through to it. Redundant messages
+/// can instead be dropped as they are handled, with `coalesce` on the handler (see below).
+///
+/// With the `with-tokio-1` feature of spaad, every `spawn` constructor also gets a variant with
+/// `_on_handle` appended to its name, which takes a `&tokio::runtime::Handle` before its other
+/// arguments and spawns the actor onto that runtime instead. This suits libraries which shouldn't
+/// assume which runtime they are called from, or processes with more than one runtime:
+///
+/// ```rust,ignore
+/// let runtime = tokio::runtime::Runtime::new()?;
+/// let actor = MyActor::new_on_handle(runtime.handle(), 1);
+/// ```
+///
+/// If the handle's runtime has already shut down, tokio drops the actor's task instead of running
+/// it. The wrapper is returned all the same, but its address is disconnected, so sending to it
+/// panics, or reports `xtra::Disconnected` for handlers returning a `Result` of it.
+///
+/// xtra has no way to reserve room in a mailbox ahead of sending, so there is no permit to send
+/// with later. For a `Sink` with backpressure, convert the address with `into_sink`. Its
+/// `poll_ready` is pending until the mailbox has taken the previous message. This only works for
+/// handlers reusing a message defined elsewhere, as the sink must be able to name it. Boxing the
+/// sink as a `dyn xtra::sink::MessageSink<M>` picks out which of them it sends:
+///
+/// ```rust,ignore
+/// let mut sink: Box<dyn MessageSink<Sample>> = Box::new(recorder.address().clone().into_sink());
+/// sink.send_all(&mut samples).await?;
+/// ```
+///
+/// An actor which needs its own address while it is constructed, such as to register itself with
+/// another actor, can take it as an argument of type `Address<Self>`, or an `Address` naming the
+/// actor. This is left out of the generated constructor, which creates the actor's context first
+/// and passes its address along, so the actor can be reached before it has handled any messages.
+/// Only one such argument can be taken, while the addresses of other actors are arguments like any
+/// other:
+///
+/// ```rust,ignore
+/// #[spaad::spawn]
+/// fn new(directory: &Directory, addr: Address<Self>) -> Worker {
+///     directory.register(addr.into()); // converted into the wrapper
+///     Worker { jobs: Vec::new() }
+/// }
+/// ```
+///
+/// An actor that keeps its own address will never be stopped for having no addresses left, so
+/// keep `addr.downgrade()` instead when it is stored. Since xtra only hands out the address of
+/// a created actor along with its `ActorManager`, this isn't possible with `create`. There,
+/// and whenever the address is only needed once the actor runs, take it in `Actor::started`
+/// with `ctx.address()` instead.
+///
+/// A constructor can also be marked with `#[spaad::reattach]`, which emits a `reattach` method on
+/// the wrapper to replace the state of the running actor with a newly constructed one.
+///
+/// ## Builders
+/// For an actor with many fields, `#[spaad::entangled(builder)]` on the struct generates a
+/// `{Name}Builder`, returned by `{Name}::builder()`. It has a setter for each field, and
+/// `build_and_spawn` constructs the actor and spawns it onto the given spawner. This returns
+/// `Err(spaad::MissingField)` if a field was never set, unless it is marked `#[spaad::default]`, in
+/// which case it is left as its `Default` value:
+///
+/// ```rust,ignore
+/// #[spaad::entangled(builder)]
+/// struct Server {
+///     port: u16,
+///     #[spaad::default]
+///     max_connections: usize,
+/// }
+///
+/// let server = Server::builder().port(8080).build_and_spawn(&mut Tokio::Global)?;
+/// ```
+///
+/// ## Testing handlers directly
+/// When compiling tests, a `__{constructor}_actor` function is emitted for each constructor as
+/// well, which returns the actor itself rather than the wrapper. Its methods can then be called
+/// directly, which bypasses the mailbox entirely:
+///
+/// ```rust,ignore
+/// #[test]
+/// fn prints() {
+///     let mut printer = Printer::__new_actor();
+///     futures::executor::block_on(printer.print("hello".to_string()));
+///     assert_eq!(printer.times, 1);
+/// }
+/// ```
+///
 /// ## Sending Messages
 /// Messages can then be sent to actors as such:
 /// ```rust,ignore
@@ -68,6 +207,11 @@ mod entangle;
 ///
 /// The output type of the future will be determined by the signature. It will be identical to the
 /// written type, except when the return is written as `Result<T, xtra::Disconnected>` (see below).
+/// The actor's own method keeps the signature as written, so a handler returning any other
+/// `Result<T, E>` can use `?` in its body as usual, including on errors converting into `E`.
+/// The response is sent back once the handler has returned, so it can't borrow from the actor or
+/// the message: references (and other lifetimes) in the return type must be `'static`, such as a
+/// `&'static str` label.
 ///
 /// If you do not want to `await` for the message to complete processing, you can do the following:
 /// ```rust,ignore
@@ -77,13 +221,632 @@ mod entangle;
 /// This will also mean that the return type will be discarded, as the receiving end of the channel
 /// will be dropped.
 ///
+/// Inside of a handler, `self` is the actor rather than the wrapper, so calling another handler or
+/// a helper method on it runs it straight away, without sending a message. A handler must not
+/// wait for a message sent to its own actor, such as through a wrapper built from `ctx.address()`:
+/// the actor handles one message at a time, so that message is only handled once the handler has
+/// returned, and waiting for it deadlocks. Such a message can be sent without waiting for it.
+///
+/// The arguments are moved into the message without being cloned, so they don't need to implement
+/// `Clone`, and large payloads are not copied. Only broadcasting clones them. Responses are moved
+/// back the same way, straight from the handler into the channel the caller waits on, so a handler
+/// can return a large `Vec<T>` (or send it through a `Reply`) without `T: Clone` or a copy.
+///
+/// Messages are handled in the order they are sent: xtra's mailbox is a single queue with no
+/// priorities, so there is no way to send a message with a lower priority to be handled only once
+/// the actor is idle, and `#[spaad::handler(background)]` is rejected. Long running maintenance,
+/// such as compaction, can instead be split up, with the handler doing a little of it and sending
+/// the rest to `ctx.address()`, which queues it behind whatever was sent in the meantime.
+///
+/// ## Subscriptions
+/// Handlers cannot return `impl Trait` (such as `impl Stream`), since the result type of the
+/// message must be nameable. To let callers subscribe to a stream of events from an actor, return
+/// an owned channel receiver from the handler, and keep the sender in the actor:
+///
+/// ```rust,ignore
+/// #[spaad::handler]
+/// fn subscribe(&mut self) -> mpsc::UnboundedReceiver<Event> {
+///     let (tx, rx) = mpsc::unbounded();
+///     self.subscribers.push(tx);
+///     rx
+/// }
+///
+/// let mut events = bus.subscribe().await;
+/// while let Some(event) = events.next().await { /* ... */ }
+/// ```
+///
+/// The stream then ends once the actor drops the sender, such as when it stops.
+///
+/// To notify other actors instead, keep their message channels in a `spaad::Subscribers` field
+/// marked `#[spaad::subscribers]`. This generates `subscribe` and `unsubscribe` methods on the
+/// wrapper, which add a channel to the field and remove it from there through the actor. As xtra's
+/// channels can't be compared, `subscribe` returns a `SubscriptionId`, which is what is passed to
+/// `unsubscribe`. `notify` on the field sends a clone of the message to each subscriber with
+/// `do_send`, and drops those which have stopped:
+///
+/// ```rust,ignore
+/// #[spaad::entangled]
+/// pub struct Feed {
+///     #[spaad::subscribers]
+///     subscribers: spaad::Subscribers<Headline>,
+/// }
+///
+/// #[spaad::handler]
+/// fn publish(&mut self, title: String) {
+///     self.subscribers.notify(Headline(title));
+/// }
+///
+/// let id = feed.subscribe(reader).await; // any actor which handles `Headline`
+/// feed.unsubscribe(id).await;
+/// ```
+///
+/// Only one field of an actor can be marked like this.
+///
+/// ## Fire and forget
+/// For actors where most handlers are events which don't need to be waited on, the impl block can
+/// be annotated with `#[spaad::entangled(fire_and_forget)]`. This makes every handler in it which
+/// returns `()` send its message with `do_send` instead, so that the generated method returns
+/// `()` immediately rather than a future. Handlers with any other return type are unaffected and
+/// still return a future as usual.
+///
+/// ```rust,ignore
+/// #[spaad::entangled(fire_and_forget)]
+/// impl Printer {
+///     #[spaad::handler]
+///     fn print(&mut self, string: String) { /* ... */ }
+///
+///     #[spaad::handler(send)]
+///     fn flush(&mut self) { /* ... */ }
+/// }
+///
+/// printer.print("hello".to_string()); // returns immediately
+/// printer.flush().await;
+/// ```
+///
+/// The attribute on the handler takes precedence: a handler marked with `#[spaad::handler(send)]`
+/// always returns a future, even in a `fire_and_forget` impl block.
+///
+/// ## Broadcasting
+/// A handler marked with `#[spaad::handler(broadcast)]` also gets an associated function named
+/// `{handler}_all`, which sends the message to each of a collection of actors at once and collects
+/// their responses. The arguments are cloned for each actor, so they must implement `Clone` (or the
+/// message must, if it is reused with `msg`).
+///
+/// ```rust,ignore
+/// #[spaad::handler(broadcast)]
+/// async fn name(&mut self, prefix: String) -> String { /* ... */ }
+///
+/// let actors: Vec<MyActor> = /* ... */;
+/// let names: Vec<Result<String, Disconnected>> = MyActor::name_all(&actors, "a".to_string()).await;
+/// ```
+///
+/// Each result is `Err(Disconnected)` if that actor had disconnected, instead of panicking.
+/// Broadcasts always wait for the responses, even in a `fire_and_forget` impl block.
+///
+/// Such a handler also gets `{handler}_do_send_all`, which takes the same arguments and sends the
+/// message to each actor without waiting for it to be handled. It is best effort: the message is
+/// still sent to the rest of the actors when one of them has disconnected, and the result for each
+/// only says whether it could be sent.
+///
+/// ```rust,ignore
+/// let sent: Vec<Result<(), Disconnected>> = MyActor::name_do_send_all(&actors, "a".to_string());
+/// ```
+///
+/// ## Streams
+/// A handler taking one argument which is marked with `#[spaad::handler(stream)]` also gets a
+/// method named `{handler}_stream`, which sends each item of a stream of arguments to the actor
+/// and returns a stream of the responses. Each message is only sent once the response to the one before it has
+/// arrived, so the responses are in the same order as the arguments. Like a broadcast, each response
+/// is `Err(Disconnected)` if the actor had disconnected, instead of panicking.
+///
+/// ```rust,ignore
+/// #[spaad::handler(stream)]
+/// async fn parse(&mut self, line: String) -> u32 { /* ... */ }
+///
+/// let numbers: Vec<Result<u32, Disconnected>> = actor.parse_stream(lines).collect().await;
+/// ```
+///
+/// Handlers in a `fire_and_forget` impl block can't be streamed unless they are also marked
+/// `send`, since there would be no responses to wait for.
+///
+/// ## Batches
+/// A handler marked with `#[spaad::handler(batch)]` also gets a method named `{handler}_batch`,
+/// which takes a `Vec` of arguments (or of tuples of them, for a handler taking more than one) and
+/// sends them to the actor in a single message. The actor handles each of them in turn, as if they
+/// had been sent one after another, but the batch only goes through the mailbox once. The
+/// responses are in the same order as the arguments, and the batch as a whole resolves to
+/// `Err(Disconnected)` if the actor had disconnected.
+///
+/// ```rust,ignore
+/// #[spaad::handler(batch)]
+/// async fn insert(&mut self, key: u64, value: u64) -> Option<u64> { /* ... */ }
+///
+/// let replaced: Vec<Option<u64>> = index.insert_batch(entries).await?;
+/// ```
+///
+/// The batch message is a `Vec` of the handler's own messages, so a guard still rejects each
+/// argument on its own. No other message is handled until the whole batch has been, so very large
+/// batches hold up whatever is sent to the actor in the meantime. Handlers reusing a message,
+/// deferring their response or catching panics cannot be batched.
+///
+/// ## Coalescing
+/// A handler spammed from many places, such as one refreshing a cache, can drop the messages made
+/// redundant by a run which started after they were sent. `#[spaad::handler(coalesce = "field")]`
+/// names a field of the actor of type `spaad::Coalesce<K>`, in which the handler's starts are kept,
+/// and `coalesce_key = "arg"` names the argument they are kept by. Without a key, all of the
+/// handler's messages share one (and the field is a `Coalesce<()>`):
+///
+/// ```rust,ignore
+/// #[spaad::handler(coalesce = "refreshes", coalesce_key = "source")]
+/// fn refresh(&mut self, source: String) { /* ... */ }
+/// ```
+///
+/// A message is dropped if the handler has started with an equal key since it was sent, and is
+/// handled otherwise. So of a flood of messages queued up while the actor was busy, only the first
+/// for each key runs, while one sent after that run has started runs again. Messages are not
+/// reordered or merged, and a dropped message is answered with the `Default` of the handler's
+/// return type, as if it had been rejected by a guard (which is checked first). The key must be
+/// `Clone`, `Eq` and `Hash`, and an entry is kept for each key until it is `forget`ten.
+///
+/// ## Predefined messages
+/// Message types defined elsewhere, such as in a module of their own, can be listed with
+/// `#[spaad::entangled(messages(...))]` on an impl block. A handler in it whose only argument
+/// (other than the context) is a type with the same name as one of them then reuses that message,
+/// as with `#[spaad::handler(msg = "...")]`, instead of generating one:
+///
+/// ```rust,ignore
+/// #[spaad::entangled(messages(messages::Print, messages::Clear))]
+/// impl Printer {
+///     #[spaad::handler]
+///     pub fn print(&mut self, print: Print) {/* ... */}
+/// }
+/// ```
+///
+/// Handlers with any other arguments generate their messages as usual, and `msg = "..."` on a
+/// handler always takes precedence.
+///
+/// Generated messages are local to the wrapper methods, so they can't be named anywhere else.
+/// Messages which need to be named, such as to serialize them with `serde` and send them to an
+/// actor on another machine, should be predefined like this, with whatever derives they need.
+///
+/// ## Message channels
+/// When a handler reuses a message defined elsewhere with `#[spaad::handler(msg = "...")]`, the
+/// wrapper can be converted into a `Box<dyn MessageChannel<...>>` for that message. This allows
+/// different actors which all handle the same message to be stored together:
+///
+/// ```rust,ignore
+/// let channels: Vec<Box<dyn MessageChannel<Command>>> = vec![printer.into(), logger.into()];
+/// ```
+///
+/// An actor which does not handle the message has no such conversion, so using it here is a
+/// compile error.
+///
+/// Such a handler also gets a method named `{handler}_sender`, which returns a `spaad::Sender<M>`
+/// for the message: a boxed closure of type
+/// `Box<dyn Fn(M) -> Pin<Box<dyn Future<Output = Result<M::Result, Disconnected>> + Send>> + Send + Sync>`.
+/// It can be stored in a dispatch table and called like a function, without knowing the actor. It
+/// holds a clone of the wrapper, so with `stop_on_last_drop` the actor keeps running until it is
+/// dropped too.
+///
+/// ```rust,ignore
+/// let mut plugins: HashMap<&str, spaad::Sender<Command>> = HashMap::new();
+/// plugins.insert("print", printer.print_sender());
+/// plugins.insert("log", logger.log_sender());
+/// let printed = (plugins["print"])(Command::new("hello")).await?;
+/// ```
+///
+/// ## API traits
+/// `#[spaad::entangled(api = "PrinterApi")]` on an impl block generates an object safe trait with
+/// an async method for each public handler in the block, and implements it for the wrapper. This
+/// lets callers use a `Box<dyn PrinterApi>`, which a mock can be swapped in for in tests. The
+/// methods return `Result<T, xtra::Disconnected>` rather than panicking, and always wait for the
+/// message to be handled. Handlers with generics of their own can't be in an object safe trait, so
+/// they are left out.
+///
+/// ```rust,ignore
+/// #[spaad::entangled(api = "PrinterApi")]
+/// impl Printer {
+///     #[spaad::handler]
+///     pub fn print(&mut self, to_print: String) {/* ... */}
+/// }
+///
+/// let printer: Box<dyn PrinterApi> = Box::new(Printer::new());
+/// printer.print("hello".to_string()).await?;
+/// ```
+///
+/// With `native_async` next to `api`, the trait is generated without `async_trait`, and its
+/// methods return the wrapper's futures as they are, as `impl Future<Output = ...> + Send`, rather
+/// than boxing them. This needs a compiler with support for `impl Trait` in trait methods (Rust
+/// 1.75 or later). Such a trait is not object safe, so it has to be used through generics, such as
+/// `fn count<C: CounterApi>(counter: &C)`, instead of as a `Box<dyn ...>`. It only applies to the
+/// trait of the impl block it is passed to, so other traits can still be used as trait objects.
+///
+/// ```rust,ignore
+/// #[spaad::entangled(api = "CounterApi", native_async)]
+/// impl Counter { /* ... */ }
+/// ```
+///
+/// ## Splitting into senders
+/// `#[spaad::entangled(senders = "PrinterSenders")]` on an impl block generates a struct with a
+/// field for each handler in the block, named after its wrapper method and with its visibility.
+/// Handlers with generics of their own are left out, since a closure can't be generic. Each field
+/// is a boxed closure, which takes the message's arguments and returns a boxed future resolving to
+/// `Result<T, xtra::Disconnected>`. The wrapper gets an `into_parts` method, which splits it into
+/// the actor's `Address` and this struct. The struct and `into_parts` have the widest visibility
+/// of the fields, so they can be used wherever one of the closures can. This is mostly useful to
+/// libraries which build routers on top of spaad, since the closures can be stored and called
+/// without knowing which actor they send to.
+///
+/// Every closure holds a clone of the wrapper, so with `stop_on_last_drop` the actor keeps running
+/// until they are all dropped too. Since the method is always named `into_parts`, only one impl
+/// block per actor can have `senders`.
+///
+/// ```rust,ignore
+/// #[spaad::entangled(senders = "PrinterSenders")]
+/// impl Printer {
+///     #[spaad::handler]
+///     pub fn print(&mut self, to_print: String) {/* ... */}
+/// }
+///
+/// let (addr, senders) = printer.into_parts();
+/// (senders.print)("hello".to_string()).await?;
+/// ```
+///
+/// ## Listing handlers
+/// `#[spaad::entangled(handlers)]` on an impl block generates a constant `HANDLERS` on the
+/// wrapper, listing the names of the handlers in the block in the order they are declared. These
+/// are the names of the wrapper methods, so renamed handlers are listed under their new name, and
+/// handlers which are `cfg`'d out are left out. `handlers = "PRINTING"` names the constant
+/// something else.
+///
+/// Each impl block lists only its own handlers, so give the constants of impl blocks for the same
+/// actor different names, and join them if all the handlers are needed, such as with
+/// `[Printer::PRINTING, Printer::ADMIN].concat()`.
+///
+/// ```rust,ignore
+/// #[spaad::entangled(handlers)]
+/// impl Printer {
+///     #[spaad::handler]
+///     pub fn print(&mut self, to_print: String) {/* ... */}
+///
+///     #[spaad::handler(rename = "flush")]
+///     pub fn do_flush(&mut self) {/* ... */}
+/// }
+///
+/// assert_eq!(Printer::HANDLERS, ["print", "flush"]);
+/// ```
+///
 /// ## Handling disconnection
 /// The methods to send messages will panic if the actor is disconnected. If you want to manually
 /// handle this error, make the return type of the handler function `Result<T, xtra::Disconnected>`.
+/// Any other return type is passed through as it is, so the `None` of a handler returning
+/// `Option<T>` is only ever the handler's own answer, such as a lookup finding nothing. Returning
+/// `Result<Option<T>, xtra::Disconnected>` keeps both apart: `Ok(None)` is a miss, while
+/// `Err(Disconnected)` means the actor has stopped.
+/// The methods are `#[track_caller]`, so a panic from a `fire_and_forget` method points at where it
+/// was called. Otherwise, the panic happens when the returned future is polled, where the caller
+/// can't be tracked, so its location is named in the panic message instead.
 /// The type must be named `Context` - it cannot be renamed by re-importing. If you want to access
 /// the actor cotnext add an argument to the function with `&mut Context<Self>` as the type.
 /// Similarly, the type must be named `Context` - it cannot be renamed by re-importing.
 ///
+/// ## Catching panics
+/// A handler which panics takes the actor down with it, and its caller only sees that the actor
+/// disconnected. With `#[spaad::entangled(catch_panics)]` on an impl block, the panics of its
+/// handlers are caught instead, and the wrapper methods return `Result<T, spaad::HandlerError>`:
+/// `HandlerError::Panicked` holds the panic's message, and `HandlerError::Disconnected` takes the
+/// place of the usual panic when the actor has already stopped. Their return types must not be
+/// `Result<T, xtra::Disconnected>`, since this already reports disconnection.
+///
+/// ```rust,ignore
+/// #[spaad::entangled(catch_panics)]
+/// impl Calculator {
+///     #[spaad::handler]
+///     pub fn divide(&mut self, a: i32, b: i32) -> i32 {
+///         a / b
+///     }
+/// }
+///
+/// assert!(matches!(calculator.divide(1, 0).await, Err(HandlerError::Panicked(_))));
+/// ```
+///
+/// The handler is run through `catch_unwind` as if it were `UnwindSafe`, which it usually isn't,
+/// since it has the actor borrowed mutably: a panic partway through an update leaves the actor's
+/// state half updated. So the actor is still stopped once the panicking handler returns, as it
+/// would have been anyway. With `catch_panics(keep_alive)`, it keeps running instead, which is only
+/// sound if the handlers leave its state valid wherever they may panic. Either way, the panic hook
+/// still runs, so the panic is printed as usual, and panics which abort can't be caught.
+///
+/// ## Deferred responses
+/// A handler waiting on something slow, such as an external service, keeps every other message
+/// waiting behind it. With `#[spaad::handler(defer)]`, the handler instead returns the future of
+/// its response, written as `impl Future<Output = T> + Send + 'static`, and the actor goes on to
+/// handle other messages as soon as the handler has returned. The actor answers with the future
+/// boxed as a `spaad::Deferred<T>`, which the wrapper method awaits before resolving to `T`, just
+/// like any other handler returning `T`. So do the API trait, senders, weak handles, broadcasts and
+/// streams, which report disconnection as usual.
+///
+/// The future must not borrow the actor, so the handler can't be `async`: take what the response
+/// needs from the actor before returning it. A deferred handler can't have a `guard`, and can't
+/// be in a `catch_panics` impl block, since the future is awaited by the caller. Mock responses
+/// are set as a `spaad::Deferred::new(...)`.
+///
+/// ```rust,ignore
+/// #[spaad::handler(defer)]
+/// pub fn fetch(&mut self, key: String) -> impl Future<Output = String> + Send + 'static {
+///     let client = self.client.clone();
+///     async move { client.get(key).await }
+/// }
+/// ```
+///
+/// A handler can also take a `spaad::Reply<T>` to send its response through: the wrapper method
+/// doesn't take it, and resolves to the `T` sent through it, however long after the handler has
+/// returned that is. The reply can be kept in the actor along with a correlation ID, or moved into
+/// a task, and makes a deferred response of its own, so the same rules apply. The handler
+/// itself must not return anything, but can be `async`. Like `Context`, the type is recognised by
+/// its name, so it must be named `Reply`. If the reply is dropped without a response, this is
+/// reported just as if the actor had stopped: the wrapper method panics, unless the response is a
+/// `Result<T, xtra::Disconnected>`, while the API trait, senders, weak handles, broadcasts and
+/// streams return `Err(Disconnected)`. Awaiting a `spaad::Deferred<T>` directly, such as one
+/// returned by calling the handler on the actor in a test, likewise resolves to a
+/// `Result<T, xtra::Disconnected>`.
+///
+/// ```rust,ignore
+/// #[spaad::handler]
+/// pub fn request(&mut self, id: u64, reply: Reply<Response>) {
+///     self.waiting.insert(id, reply);
+/// }
+/// ```
+///
+/// ## Weak handles
+/// With `#[spaad::entangled(weak)]` on the actor struct, `{Name}Weak` and `{Name}Handle` are also
+/// generated. The wrapper's `downgrade` returns a `{Name}Weak`, which does not keep the actor
+/// alive, and `{Name}Handle` is an enum of the two which both convert into. Both have a method for
+/// each handler of the impl blocks which are passed `weak` as well, which returns
+/// `Err(xtra::Disconnected)` if the actor has stopped:
+///
+/// ```rust,ignore
+/// let handles: Vec<PrinterHandle> = vec![printer.clone().into(), printer.downgrade().into()];
+/// for handle in &handles {
+///     handle.print("hello".to_string()).await?;
+/// }
+/// ```
+///
+/// `{Name}Weak::upgrade` returns the strong wrapper again, or `None` if the actor has stopped. Only
+/// the actor can get a strong address from a weak one, so this sends it a hidden message, whose
+/// handler is generated alongside the `Actor` implementation, and must be awaited. Since the actor
+/// handles one message at a time, it either answers while it is still running or not at all, so
+/// an upgraded wrapper never points at a stopped actor. With `stop_on_last_drop`, the upgraded
+/// wrapper shares the guard of the other wrappers, rather than getting its own:
+///
+/// ```rust,ignore
+/// let weak = printer.downgrade();
+/// if let Some(printer) = weak.upgrade().await {
+///     printer.print("hello".to_string()).await;
+/// }
+/// ```
+///
+/// The wrapper also downgrades through `From`, into either `{Name}Weak` or the actor's
+/// `xtra::WeakAddress`. There is no `TryFrom` the other way, since a conversion can't wait for the
+/// actor to answer, so upgrading is always done with `upgrade`.
+///
+/// ## Mocks
+/// With `#[spaad::entangled(mock)]` on the struct, a `Mock{Name}` is also generated under
+/// `#[cfg(test)]`. It has the handler methods of the impl blocks also marked `mock`, but records
+/// each call instead of sending a message. The response of a handler is set with `on_{handler}`,
+/// which is given the handler's arguments as a tuple. Handlers which return `()` don't need a
+/// response, but calling any other handler without one panics:
+///
+/// ```rust,ignore
+/// #[spaad::entangled(mock)]
+/// pub struct Printer {
+///     lines: usize,
+/// }
+///
+/// #[spaad::entangled(mock)]
+/// impl Printer {
+///     // ...
+/// }
+///
+/// let printer = MockPrinter::new();
+/// printer.on_lines(|_| 3);
+/// printer.print("hello".to_string()).await;
+///
+/// assert_eq!(printer.lines().await, 3);
+/// assert_eq!(printer.calls()[0].handler(), "print");
+/// assert_eq!(printer.calls()[0].args::<(String,)>(), Some(&("hello".to_string(),)));
+/// ```
+///
+/// ## Attributes on the wrapper
+/// Attributes on the actor struct, such as `#[derive(...)]`, are applied to the actor itself and
+/// not the generated wrapper. To put an attribute on the wrapper instead, wrap it in
+/// `#[spaad::wrapper_attr(...)]`:
+///
+/// ```rust,ignore
+/// #[spaad::entangled]
+/// #[derive(Clone)] // derives `Clone` for the actor
+/// #[spaad::wrapper_attr(derive(Injectable))] // derives `Injectable` for the wrapper
+/// struct MyActor {
+///     x: u32,
+/// }
+/// ```
+///
+/// These attributes are emitted on the wrapper struct definition in the order that they were
+/// written, so derives on the wrapper will only see its address field. `Clone` is always
+/// implemented for the wrapper by spaad, so a `Clone` derived through `spaad::wrapper_attr` is
+/// left out instead of conflicting with it. A `#[derive(Clone)]` on the actor struct never
+/// conflicts, as it only makes the actor itself `Clone`, such as to snapshot its state.
+///
+/// ## Generic actors
+/// An actor can be generic, like any other struct, and its handler impl blocks can be either
+/// generic over it or for just one instantiation of it, such as `impl Cache<u32>`. The generated
+/// code always names the actor with the type arguments written in the self type, so the handlers
+/// in such a block are only on `Cache<u32>`'s wrapper. Every handler has messages of its own, so
+/// handlers with the same name in blocks for different instantiations don't conflict.
+///
+/// xtra requires actors to be `Send + 'static`, so every type parameter of the actor is bounded by
+/// that on the wrapper and everything generated for it, including the type parameters of the
+/// actor on every `#[spaad::entangled]` impl block. These bounds don't need to be written out,
+/// though they can be, and any others still do. A `Cache<T>` is only an actor when
+/// `T: Send + 'static`, such as `Cache<String>`. The actor struct itself keeps the bounds it was
+/// written with, so an impl written by hand for it, such as of `Debug`, doesn't need the others.
+///
+/// `Self` can be bounded in the where clause of a handler impl block, as in `where Self: Clone`,
+/// and means the actor there. The bounds are also put on what is generated for the wrapper, with
+/// `Self` spelled out as the actor, so its handlers are only there when the actor meets them.
+///
+/// Handlers can take and return the actor's type parameters and their associated types, such as
+/// `fn snapshot(&self) -> S::Snapshot` for `S: Store`. Their messages are then generic over the
+/// parameters of the impl block, and have its bounds, so those needed by the associated types
+/// (such as `S::Snapshot: Send`) have to be written on it or on the trait. This also works for
+/// a type parameter which the actor only holds as a `PhantomData`, and otherwise only uses in the
+/// signatures of its handlers.
+///
+/// Default generic parameters, such as `struct Buffer<T = u8>`, are kept on the wrapper and
+/// everything else generated for the struct, so `Buffer` names `Buffer<u8>`'s wrapper.
+///
+/// Wrappers for different instantiations are different types, and can't be converted into each
+/// other, as they point at different actors. Use an API trait to treat them alike.
+///
+/// ## Trait implementations
+/// Traits are implemented for the actor itself by annotating the impl block with
+/// `#[spaad::entangled]`, and can then be used from inside of its handlers. The trait can have
+/// generic parameters of its own alongside those of the actor:
+///
+/// ```rust,ignore
+/// #[spaad::entangled]
+/// impl<T, I: Into<i32>> Extend<I> for MyActor<T> {
+///     fn extend<It: IntoIterator<Item = I>>(&mut self, iter: It) { /* ... */ }
+/// }
+/// ```
+///
+/// Only the self type of the impl block is changed, so the methods keep their generics, where
+/// clauses and `impl Trait` arguments as they are written, and `Self` in them refers to the actor.
+///
+/// ## Other attributes on impl blocks
+/// Attribute macros written above `#[spaad::entangled]` are expanded first, and so see the impl
+/// block as it was written. Those written below it are expanded afterwards. On a handler impl
+/// block, they are kept on the actor's own impl block, which holds the methods as they were
+/// written, and not on the generated wrapper methods. `#[cfg(...)]` is the exception, and applies
+/// to everything generated from the impl block.
+///
+/// ```rust,ignore
+/// #[spaad::entangled]
+/// #[my_logging_macro] // runs on the actor's impl block, after spaad
+/// impl MyActor {
+///     #[spaad::handler]
+///     fn print(&mut self) { /* ... */ }
+/// }
+/// ```
+///
+/// Trait impls such as `#[async_trait::async_trait] impl Actor for MyActor` are emitted for the
+/// actor with all of their attributes in place, so `async_trait` should be written below
+/// `#[spaad::entangled]`, as in the examples.
+///
+/// ## Impl blocks only for the actor
+/// An impl block marked with `#[spaad::entangled(inner_only)]` is only implemented on the actor,
+/// and nothing is generated for the wrapper from it, not even for its functions without a
+/// receiver. This keeps helpers which are only used by the actor's handlers out of the wrapper.
+/// It cannot declare handlers or constructors.
+///
+/// ```rust,ignore
+/// #[spaad::entangled(inner_only)]
+/// impl Printer {
+///     fn format(&self, string: &str) -> String { /* ... */ }
+/// }
+/// ```
+///
+/// ## Conditional spaad attributes
+/// spaad's attributes on methods can be applied conditionally with `cfg_attr`:
+///
+/// ```rust,ignore
+/// #[spaad::entangled(fire_and_forget)]
+/// impl MyActor {
+///     #[spaad::handler]
+///     #[cfg_attr(feature = "strict", spaad::handler(send))] // waited on only with `strict`
+///     fn print(&mut self) { /* ... */ }
+/// }
+/// ```
+///
+/// Since the compiler evaluates `cfg_attr` after spaad has run, spaad emits the wrapper method
+/// twice, once with the attributes and once without, each gated on the predicate. Because of
+/// this, only one `cfg_attr` containing spaad attributes is allowed per method.
+///
+/// ## Naming the actor
+/// By default, the actor struct has the same name as the wrapper. A different name can be given to
+/// it with `actor_name`, which makes it show up under that name in debug output and type names:
+///
+/// ```rust,ignore
+/// #[spaad::entangled(actor_name = "DatabaseState")]
+/// #[derive(Debug)] // prints as `DatabaseState { .. }`
+/// struct Database {/* ... */}
+/// ```
+///
+/// The impl blocks should still be written for the wrapper's name (`Database` above).
+///
+/// Every name spaad generates is derived from the names written in its input: the actor's module
+/// is `__{Name}Actor`, the generated types are `{Name}Builder`, `{Name}Weak` and so on, and the
+/// messages are declared inside of the wrapper methods, so they need no names of their own.
+/// Nothing is numbered in the order the macros happen to run, so the same input always expands to
+/// the same names, in every crate and every compilation.
+///
+/// ## Displaying the wrapper
+/// `#[spaad::entangled(display)]` on the actor struct implements `Display` for the wrapper. It
+/// prints the name of the actor along with whether it is still connected, such as
+/// `Printer(connected)`, so the actor itself does not need to implement `Display`. This is not an
+/// identifier for the actor, since it is the same for every actor of the type and changes once
+/// the actor stops. xtra gives its addresses no identity to print instead.
+///
+/// ## Accessing the address
+/// The wrapper holds the actor's `xtra::Address` in a private field named `addr`. `address`,
+/// `owned_address` and `into_address` on the wrapper are the supported ways to get at it, which
+/// borrow it, clone it and take it out of the wrapper respectively. The wrapper also converts to
+/// and from the address with `Into` and `From`. With `#[spaad::entangled(expose_addr)]` on the
+/// actor struct, the field is made as visible as the struct itself, so that the wrapper can also be
+/// taken apart or built with a struct literal, such as `Printer { addr }`. Since every wrapper has
+/// these methods, a handler with one of their names is an error; its wrapper method is given
+/// another name with `rename`, such as `#[spaad::handler(rename = "actor_address")]`.
+///
+/// To leave room for fields being added to the wrapper later, mark it `#[non_exhaustive]` with
+/// `#[spaad::wrapper_attr(non_exhaustive)]`. Other crates can then still read an exposed `addr`,
+/// but can only build the wrapper with `From`, which is unaffected, while the crate defining it
+/// can still use struct literals. A plain `#[non_exhaustive]` on the actor struct applies to the
+/// actor, like any other attribute.
+///
+/// xtra's addresses can't be compared with each other, so neither can the wrappers: there is no
+/// way to tell whether two of them are for the same actor. To tell actors apart, give each an id
+/// when it is constructed, and keep it next to the wrapper or return it from a handler.
+///
+/// ## Visibility
+/// The generated wrapper methods have the same visibility as the methods that they were generated
+/// from, so a `pub(crate)` handler can be called from anywhere in the crate, but not outside of
+/// it. Since the actor itself is moved into a hidden module, the fields of the actor struct are
+/// made `pub(super)` so that they can still be accessed by its impl blocks, unless they are
+/// `pub`. This only changes the fields of the actor, and not the methods of the wrapper.
+///
+/// The actor of a `pub` wrapper is only `pub(crate)`, so other crates can't name or construct it,
+/// and can only reach it through the wrapper. The wrapper's methods and conversions which hand out
+/// or take an `xtra::Address` or `xtra::WeakAddress` of the actor still work in other crates,
+/// though they can't write out its type: `address`, `owned_address`, `into_address`, the `Into`
+/// and `From` conversions, `into_parts` of the senders, and the `address` and `Into` of the weak
+/// wrapper. Anything which names the actor, such as trait impls or `Handler` impls for other
+/// messages, must therefore be written in the crate that declares it.
+///
+/// ## `Send` bounds
+/// xtra requires actors, messages and their results to be `Send`, and the futures of handlers are
+/// boxed as `Send` by `async_trait`. The generated messages and `Handler` impls therefore always
+/// have the same `Send` requirements, whichever runtime spaad spawns onto, and there is no mode for
+/// actors which are not `Send`. The type parameters of generic actors are bounded by `Send`
+/// for them, as described under [generic actors](#generic-actors). Those of a generic handler
+/// are not, and need `Send + 'static` like anything else in a message. They can be bounded
+/// inline or in the method's where clause, which is kept on its message and wrapper methods. An
+/// argument which is not `Send + 'static` is reported at its type in the handler's signature, as
+/// well as at the generated message.
+///
+/// Because of this, the futures returned by the wrapper methods are always `Send` too, and
+/// `clippy::future_not_send` does not fire on generated code. spaad emits no `allow` for it, so it
+/// still catches non-`Send` futures in the rest of the crate.
+///
 /// ## Implementations in other modules
 /// To implement something on an actor in a module other than where it is declared, you will need
 /// to refer to it either by its fully-qualified path (e.g `crate::actor::MyActor`) or a local path
@@ -105,10 +868,170 @@ mod entangle;
 /// This is a limitation due to how the macro expands, and should be resolved when there is support
 /// for inherent-impl type aliases (see [rust/60471](https://github.com/rust-lang/rfcs/issues/1697)).
 /// This is currently blocked on lazy normalization.
+///
+/// Alternatively, the path to the actor can be given with `actor = "..."`, in which case the self
+/// type can be spelled however it is in scope, such as through an import or an alias. It must still
+/// name the same wrapper as the path, or a type mismatch is reported at the self type:
+///
+/// ```rust,ignore
+/// use crate::actor::MyActor;
+/// #[spaad::entangled(actor = "crate::actor::MyActor")]
+/// impl MyActor { /* ... */ }
+/// ```
+///
+/// The macro only sees the name written as the self type, and can't tell that it is a type alias,
+/// so it looks for an actor named after the alias itself. Once the alias is resolved, this is
+/// caught with an error at the self type, alongside the ones about an unresolved `__{Alias}Actor`
+/// module. The alias has to be seen through with `actor = "..."`, giving the type the alias stands
+/// for along with its type arguments:
+///
+/// ```rust,ignore
+/// type Counter = GenericCounter<u32>;
+///
+/// #[spaad::entangled(actor = "GenericCounter<u32>")]
+/// impl Counter { /* ... */ }
+/// ```
+///
+/// ## Using spaad under another name
+/// The generated code names spaad as `::spaad`, which doesn't resolve when it is a renamed
+/// dependency or only re-exported, such as by a facade crate. Passing the path to use instead with
+/// `crate = "..."` on each entangled struct and impl block replaces `::spaad` in all of their
+/// generated code, and lets spaad's other attributes on their items start with that path too:
+///
+/// ```rust,ignore
+/// #[my_spaad::entangled(crate = "my_spaad")]
+/// pub struct Counter { /* ... */ }
+///
+/// #[my_spaad::entangled(crate = "my_spaad")]
+/// impl Counter {
+///     #[my_spaad::handler]
+///     pub fn add(&mut self, n: u64) -> u64 { /* ... */ }
+/// }
+/// ```
+///
+/// The path is used as written, and is also used from inside of the actor's module, so a path to a
+/// re-export must be absolute, as in `crate = "crate::deps::spaad"`. Inside of `spaad::entangle!`
+/// or an entangled module, each item is passed the path with its own `#[spaad::entangled(...)]`.
+///
+/// ## Actors declared in functions
+/// The actor is normally moved into a hidden module next to the wrapper, which can't see items
+/// declared inside of a function. For an actor declared in a function, such as within a test, pass
+/// `no_module` to `#[spaad::entangled(...)]` on the struct and each of its impl blocks. The actor
+/// is then declared next to the wrapper as `__{Name}Actor` (or with the name given by
+/// `actor_name`), which is also the name it is printed with by a derived `Debug`. Inside of
+/// `spaad::entangle! { ... }`, `no_module` only needs to be passed to the struct:
+///
+/// ```rust,ignore
+/// #[test]
+/// fn greets() {
+///     struct Greeting(&'static str);
+///
+///     spaad::entangle! {
+///         #[spaad::entangled(no_module)]
+///         struct Greeter {
+///             greeting: Greeting,
+///         }
+///
+///         impl Actor for Greeter {}
+///         impl Greeter { /* ... */ }
+///     }
+/// }
+/// ```
 #[proc_macro_error::proc_macro_error]
 #[proc_macro_attribute]
-pub fn entangled(_args: TokenStream, input: TokenStream) -> TokenStream {
-    entangle::entangle(input)
+pub fn entangled(args: TokenStream, input: TokenStream) -> TokenStream {
+    entangle::entangle(args, input)
+}
+
+/// Entangles several actors at once. Every struct and impl block inside of it is treated as if it
+/// were annotated with `#[spaad::entangled]`, and anything else is left as it is. This keeps
+/// actors which are tightly coupled, such as two which hold each other's wrappers, together.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// spaad::entangle! {
+///     pub struct Ping {
+///         pong: Option<Pong>,
+///     }
+///
+///     pub struct Pong {
+///         ping: Ping,
+///     }
+///
+///     impl Actor for Ping {}
+///     impl Actor for Pong {}
+///
+///     // arguments are still passed with the attribute
+///     #[spaad::entangled(fire_and_forget)]
+///     impl Ping {
+///         /* ... */
+///     }
+/// }
+/// ```
+///
+/// Impl blocks are matched to actors by their self type, just as with the attribute, so they don't
+/// need to follow the struct.
+///
+/// A module full of actors can instead be annotated with `#[spaad::entangled]`, which treats its
+/// items the same way. The module's items must be written inline, and arguments can't be passed
+/// to the attribute on the module itself, only on its items. Modules nested inside of it are left
+/// as they are. In either form, a struct or impl block marked `#[spaad::skip]` is not entangled:
+///
+/// ```rust,ignore
+/// #[spaad::entangled]
+/// pub mod actors {
+///     pub struct Ping { /* ... */ }
+///     pub struct Pong { /* ... */ }
+///
+///     impl Actor for Ping {}
+///     impl Actor for Pong {}
+///
+///     #[spaad::skip]
+///     pub struct Ball;
+/// }
+/// ```
+#[proc_macro_error::proc_macro_error]
+#[proc_macro]
+pub fn entangle(input: TokenStream) -> TokenStream {
+    entangle::entangle_many(input)
+}
+
+/// Generates a wrapper for an actor which already implements `xtra::Actor` and its handlers, such
+/// as one from another crate. The actor is left where it is, and only the wrapper and the methods
+/// forwarding to it are generated. The wrapper is declared as a tuple struct of the actor, and
+/// each forwarded method is declared without a body, taking `&self` and an existing message:
+///
+/// ```rust,ignore
+/// spaad::wrap! {
+///     pub struct Counter(other_crate::Counter);
+///
+///     impl Counter {
+///         pub fn increment(&self, msg: other_crate::Increment) -> u32;
+///
+///         // reports disconnection instead of panicking
+///         pub fn reset(&self, msg: other_crate::Reset) -> Result<(), xtra::Disconnected>;
+///     }
+/// }
+///
+/// let counter: Counter = other_crate::Counter::default()
+///     .create(None)
+///     .spawn(&mut xtra::spawn::Tokio::Global)
+///     .into();
+/// assert_eq!(counter.increment(other_crate::Increment).await, 1);
+/// ```
+///
+/// The return type of a forwarded method is the result of its message, and the methods are used
+/// just like those generated for handlers, returning a future of the response. Like them, they
+/// panic if the actor is disconnected, unless the return type is written as
+/// `Result<T, xtra::Disconnected>` for a message with the result `T`. The wrapper is constructed
+/// from the address of the actor, which it converts into and from, and has the same `address` and
+/// `into_address` methods as the wrappers of entangled actors. Attributes on the struct are
+/// applied to the wrapper, since there is no actor struct to apply them to.
+#[proc_macro_error::proc_macro_error]
+#[proc_macro]
+pub fn wrap(input: TokenStream) -> TokenStream {
+    wrap::wrap(input)
 }
 
 // The below attributes are just markers, so they just strip themselves from the output and output
@@ -129,13 +1052,142 @@ pub fn entangled(_args: TokenStream, input: TokenStream) -> TokenStream {
 /// // will reuse an existing message
 /// #[spaad::handler(msg = "AMsg")]
 /// async fn do_something_with_a_msg(&mut self, msg: AMsg) {/* ... */}
+///
+/// // will also generate `MyActor::do_something_broadcast_all(actors, str)` and
+/// // `MyActor::do_something_broadcast_do_send_all(actors, str)`
+/// #[spaad::handler(broadcast)]
+/// async fn do_something_broadcast(&mut self, str: String) {/* ... */}
+///
+/// // will also generate `do_something_batched_batch(vec![str1, str2])`
+/// #[spaad::handler(batch)]
+/// async fn do_something_batched(&mut self, str: String) {/* ... */}
+///
+/// // will always wait for the message to be handled, even in a `fire_and_forget` impl block
+/// #[spaad::handler(send)]
+/// async fn do_something_and_wait(&mut self) {/* ... */}
+///
+/// // the wrapper method will be called `get`, while the actor's is still `do_get`
+/// #[spaad::handler(rename = "get")]
+/// async fn do_get(&mut self) -> u32 {/* ... */}
+///
+/// // the wrapper method takes `impl Into<Arc<[u8]>>`, so it can be called with a `&[u8]`
+/// #[spaad::handler(into)]
+/// async fn write(&mut self, data: Arc<[u8]>) {/* ... */}
+///
+/// // the wrapper method takes `Arc<Config>`, and the handler borrows from it
+/// #[spaad::handler(arc_args)]
+/// async fn reconfigure(&mut self, config: &Config) {/* ... */}
+///
+/// // will only be handled if `self.is_open(&n)` returns `true`, and otherwise returns `None`
+/// #[spaad::handler(guard = "is_open")]
+/// async fn take(&mut self, n: usize) -> Option<Vec<u8>> {/* ... */}
+/// fn is_open(&self, n: &usize) -> bool {/* ... */}
+///
+/// // will not run again for a `name` it has started with since the message was sent
+/// #[spaad::handler(coalesce = "lookups", coalesce_key = "name")]
+/// async fn look_up(&mut self, name: String) {/* ... */}
 /// ```
+///
+/// With `into`, every argument of the wrapper methods, other than the context, takes
+/// `impl Into<T>` instead of its type `T`, and is converted before the message is built. This
+/// makes handlers for shared types such as `Arc<[u8]>` or `Bytes` as convenient to call as ones
+/// taking an owned buffer, without copying data that is already shared. The methods of an API
+/// trait still take `T`, since the trait must be object safe.
+///
+/// Messages must own their data, so handlers usually can't take references. With `arc_args`, an
+/// argument of type `&T` is sent as an `Arc<T>` instead, which the wrapper methods take, and the
+/// handler is given a reference into it. The caller can then share data with the actor by cloning
+/// the `Arc`, and `T` does not need to implement `Clone`, even when broadcasting. `&'static`
+/// references and the context are left as they are, and `&mut T` is not supported.
+///
+/// A guard is a method of the actor taking `&self` and a reference to each of the handler's
+/// arguments, except for the context, and returning a `bool`. When it returns `false`, the message
+/// is answered with `Default::default()` without the handler running, so the handler's return type
+/// must implement `Default`.
+///
+/// `Self` cannot appear in the arguments or return type of a handler. Inside of the message it
+/// would name the wrapper, but inside of the handler the actor, and the actor's state cannot be
+/// sent in a message.
+///
+/// A `#[deprecated]` handler deprecates each of the methods generated for it, including those of
+/// the weak handles, mocks and API trait, so callers are warned wherever they call it. The code
+/// generated inside of them doesn't warn. Likewise, a `#[doc(hidden)]` handler can still be
+/// called, but none of its methods show up in the documentation, including its broadcasts,
+/// stream, sender, API trait method and senders field.
+///
+/// Handlers cannot be `unsafe`. Methods which aren't handlers are kept on the actor, and those
+/// without a receiver (including `const fn`s) are also forwarded to from the wrapper, so that
+/// `MyActor::helper()` can still be called. Associated consts are forwarded to in the same way:
+/// the wrapper's copy takes its value from the actor's, so `Self::MAX` always means the actor's,
+/// even in consts using items of `inner_only` impl blocks. Consts whose type mentions `Self` are
+/// left on the actor alone.
+///
+/// Arguments of a handler can use patterns, such as `(key, value): (String, u32)` or `_: Token`.
+/// The wrapper methods then take the argument as a whole, and the handler destructures it as usual.
+///
+/// Attributes on an argument, such as `#[allow(...)]`, are kept on the actor's method, and copied
+/// onto the argument of each wrapper method and onto its field in the generated message. The
+/// message doesn't derive anything, so attributes which are read by a derive, such as
+/// `#[serde(default)]`, have nothing to apply to; use a predefined message with the derive for
+/// those instead. Arguments cannot be gated with `cfg` or `cfg_attr`, since the message would no
+/// longer match the handler.
+///
+/// Handlers can return the wrappers of other actors, such as a factory returning a newly spawned
+/// child. The return type is used in the message as it is written, so it can be any path to the
+/// other wrapper, with its generics. Likewise, boxed closures and futures, such as
+/// `Box<dyn Fn() -> BoxFuture<'static, T> + Send>`, can be returned as continuations for the
+/// caller to run.
+///
+/// The message generated for a handler is local to its wrapper method, so messages never collide,
+/// even between handlers of the same name in different impl blocks. Such handlers do still clash
+/// as methods of the actor and the wrapper, which the compiler reports as duplicate definitions
+/// pointing at both handlers.
 #[proc_macro_attribute]
 pub fn handler(_args: TokenStream, input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as ImplItemMethod);
     TokenStream::from(quote!(#input))
 }
 
+/// This marks an attribute on an actor struct as belonging to the generated wrapper, rather than
+/// the actor itself. It must be written below `#[spaad::entangled]`, which removes it.
+///
+/// ## Usage
+///
+/// ```ignore
+/// #[spaad::entangled]
+/// #[spaad::wrapper_attr(derive(MyDerive))]
+/// struct MyActor {/* ... */}
+/// ```
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_attribute]
+pub fn wrapper_attr(_args: TokenStream, _input: TokenStream) -> TokenStream {
+    proc_macro_error::abort_call_site!(
+        "`spaad::wrapper_attr` must be used on a struct below `#[spaad::entangled]`"
+    )
+}
+
+/// This marks a struct or impl block inside of a module annotated with `#[spaad::entangled]`, or
+/// of `spaad::entangle!`, to be left as it is written rather than entangled. It is removed by the
+/// enclosing macro.
+///
+/// ## Usage
+///
+/// ```ignore
+/// #[spaad::entangled]
+/// mod actors {
+///     #[spaad::skip]
+///     pub struct Config {/* ... */}
+/// }
+/// ```
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_attribute]
+pub fn skip(_args: TokenStream, _input: TokenStream) -> TokenStream {
+    proc_macro_error::abort_call_site!(
+        "`spaad::skip` must be used on an item inside of a module annotated with \
+         `#[spaad::entangled]` or of `spaad::entangle!`"
+    )
+}
+
 /// This marks a function as the method that should be used to create and spawn the actor. It must
 /// return the type of the actor, either as `Self` or by its name.
 ///
@@ -144,6 +1196,15 @@ pub fn handler(_args: TokenStream, input: TokenStream) -> TokenStream {
 /// This macro can be passed what to rename the method in the form of `rename = "{new name}"`.
 /// This is most useful when generating both a create and spawn method from the same constructor.
 ///
+/// By default, the emitted method takes a `&mut` reference to an `xtra::spawn::Spawner` as its
+/// last argument. A runtime can instead be chosen up front by passing `spawner = "{runtime}"`,
+/// where the runtime is one of `tokio`, `async_std`, `smol`, or `wasm_bindgen`. This requires the
+/// corresponding feature of spaad (`with-tokio-1`, `with-async_std-1`, `with-smol-1`, or
+/// `with-wasm_bindgen-0_2`) to be enabled, and naming a runtime without it is a compile error.
+/// This is a breaking change from earlier versions of spaad, where enabling the feature of xtra
+/// alone was enough. With `with-tokio-1`, a `{name}_on_handle` method is emitted as well, taking a
+/// `&tokio::runtime::Handle` to spawn onto as its first argument.
+///
 /// ## Usage
 ///
 /// ```ignore
@@ -156,6 +1217,11 @@ pub fn handler(_args: TokenStream, input: TokenStream) -> TokenStream {
 ///  fn spawn2(some: Thing) -> MyActor {
 ///      MyActor { some }
 ///  }
+///
+/// #[spaad::spawn(spawner = "tokio")] // Spawns onto tokio without taking a spawner
+/// fn spawn_on_tokio(some: Thing) -> MyActor {
+///     MyActor { some }
+/// }
 /// ```
 #[proc_macro_attribute]
 pub fn spawn(_args: TokenStream, input: TokenStream) -> TokenStream {
@@ -189,3 +1255,35 @@ pub fn create(_args: TokenStream, input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as ImplItemMethod);
     TokenStream::from(quote!(#input))
 }
+
+/// This marks a function as a constructor to replace the state of a running actor with. It emits a
+/// method on the wrapper, called `reattach` by default, which constructs the new state and swaps it
+/// in for the old one. The address and mailbox are kept, so messages sent after it are handled by
+/// the new state without any downtime, and every existing wrapper keeps working.
+///
+/// The old state has `Actor::stopped` called on it once it has been replaced, and the new state
+/// has `Actor::started` called on it. Other than that, the actor is not restarted: notifications
+/// scheduled through the context by the old state are delivered to the new one, and
+/// `Actor::stopping` is not called.
+///
+/// ## Arguments
+///
+/// This macro can be passed what to rename the method in the form of `rename = "{new name}"`.
+///
+/// ## Usage
+///
+/// ```ignore
+/// #[spaad::spawn]
+/// #[spaad::reattach]
+/// fn new(config: Config) -> MyActor {
+///     MyActor { config }
+/// }
+///
+/// // later, to reload the actor's logic without interrupting it:
+/// my_actor.reattach(new_config).await;
+/// ```
+#[proc_macro_attribute]
+pub fn reattach(_args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as ImplItemMethod);
+    TokenStream::from(quote!(#input))
+}
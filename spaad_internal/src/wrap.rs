@@ -0,0 +1,245 @@
+use crate::entangle::transform::{disconnected_panic, transform_ret};
+use crate::entangle::ty_is_name;
+use proc_macro::TokenStream;
+use proc_macro_error::abort;
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::parse_macro_input;
+use syn::spanned::Spanned;
+use syn::*;
+
+/// The input to `spaad::wrap!`: the wrapper around the actor, and the impl blocks declaring the
+/// methods which forward messages to it.
+struct Wrapped {
+    wrapper: ItemStruct,
+    impls: Vec<WrappedImpl>,
+}
+
+impl Parse for Wrapped {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let wrapper = input.parse()?;
+        let mut impls = Vec::new();
+        while !input.is_empty() {
+            impls.push(input.parse()?);
+        }
+
+        Ok(Wrapped { wrapper, impls })
+    }
+}
+
+/// An impl block of the wrapper, whose methods are declared without bodies.
+struct WrappedImpl {
+    attrs: Vec<Attribute>,
+    generics: Generics,
+    self_ty: Type,
+    methods: Vec<ForwardedMethod>,
+}
+
+impl Parse for WrappedImpl {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        input.parse::<Token![impl]>()?;
+        let mut generics: Generics = input.parse()?;
+        let self_ty = input.parse()?;
+        generics.where_clause = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let mut methods = Vec::new();
+        while !content.is_empty() {
+            methods.push(content.parse()?);
+        }
+
+        Ok(WrappedImpl {
+            attrs,
+            generics,
+            self_ty,
+            methods,
+        })
+    }
+}
+
+/// A method which sends its argument, an existing message, to the actor.
+struct ForwardedMethod {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    sig: Signature,
+}
+
+impl Parse for ForwardedMethod {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let sig = input.parse()?;
+        input.parse::<Token![;]>()?;
+
+        Ok(ForwardedMethod { attrs, vis, sig })
+    }
+}
+
+pub fn wrap(input: TokenStream) -> TokenStream {
+    let Wrapped { wrapper, impls } = parse_macro_input!(input as Wrapped);
+    let ItemStruct {
+        attrs,
+        vis,
+        ident,
+        generics,
+        fields,
+        ..
+    } = wrapper;
+
+    let actor = match &fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+        _ => abort!(
+            fields,
+            "the wrapper must be declared with the type of the actor as its only field";
+            help = "declare it like `pub struct MyActor(other_crate::MyActor);`"
+        ),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let impls = impls.into_iter().map(|block| {
+        if !ty_is_name(&block.self_ty, &ident.to_string()) {
+            abort!(
+                block.self_ty,
+                "impl blocks in `spaad::wrap!` must be for the wrapper `{}`",
+                ident
+            );
+        }
+
+        let WrappedImpl {
+            attrs,
+            generics,
+            self_ty,
+            methods,
+        } = block;
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let methods = methods.into_iter().map(forward_method);
+
+        quote! {
+            #(#attrs)*
+            #[automatically_derived]
+            impl#impl_generics #self_ty #where_clause {
+                #(#methods)*
+            }
+        }
+    });
+
+    let expanded = quote! {
+        #(#attrs)*
+        #vis struct #ident#generics #where_clause {
+            addr: ::spaad::export::xtra::Address<#actor>,
+        }
+
+        #[automatically_derived]
+        impl#impl_generics Clone for #ident#ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                Self {
+                    addr: self.addr.clone(),
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics #ident#ty_generics #where_clause {
+            #vis fn address(&self) -> &::spaad::export::xtra::Address<#actor> {
+                &self.addr
+            }
+
+            #vis fn into_address(self) -> ::spaad::export::xtra::Address<#actor> {
+                self.addr
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics Into<::spaad::export::xtra::Address<#actor>>
+            for #ident#ty_generics
+        #where_clause {
+            fn into(self) -> ::spaad::export::xtra::Address<#actor> {
+                self.addr
+            }
+        }
+
+        #[automatically_derived]
+        impl#impl_generics From<::spaad::export::xtra::Address<#actor>>
+            for #ident#ty_generics
+        #where_clause {
+            fn from(addr: ::spaad::export::xtra::Address<#actor>) -> Self {
+                Self { addr }
+            }
+        }
+
+        #(#impls)*
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// A forwarded method, which sends the message just like the wrapper method of a handler.
+fn forward_method(method: ForwardedMethod) -> proc_macro2::TokenStream {
+    let ForwardedMethod { attrs, vis, sig } = method;
+
+    if let Some(asyncness) = &sig.asyncness {
+        abort!(
+            asyncness,
+            "forwarded methods cannot be `async`";
+            help = "they are declared as if they were handlers, and return a future of the response"
+        );
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(recv)) if recv.reference.is_some() && recv.mutability.is_none() => {}
+        _ => abort!(sig, "forwarded methods must take `&self`"),
+    }
+
+    let msg = match (inputs.next(), inputs.next()) {
+        (Some(FnArg::Typed(PatType { pat, .. })), None) => match &**pat {
+            Pat::Ident(pat) => &pat.ident,
+            _ => abort!(pat, "the message must be bound to a name"),
+        },
+        _ => abort!(
+            sig.inputs,
+            "forwarded methods must take the message as their only argument";
+            help = "declare it like `pub fn increment(&self, msg: Increment) -> u32;`"
+        ),
+    };
+
+    // The message's result is unchanged, unless the return type reports disconnection, in which
+    // case it is the result of `send` itself
+    let (output, handle_result) = match (&sig.output, transform_ret(&sig.output)) {
+        (ReturnType::Type(_, ty), Some(_)) => (quote!(#ty), quote!()),
+        (ReturnType::Type(_, ty), None) => (quote!(#ty), disconnected_panic()),
+        (ReturnType::Default, _) => (quote!(()), disconnected_panic()),
+    };
+    // Spanned so that a return type which doesn't match the message's result is pointed at
+    let response =
+        quote_spanned!(sig.output.span()=> let response: #output = f.await#handle_result;);
+
+    let Signature {
+        ident,
+        generics,
+        inputs,
+        ..
+    } = &sig;
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #(#attrs)*
+        #[track_caller]
+        #vis fn #ident#impl_generics(
+            #inputs
+        ) -> impl ::std::future::Future<Output = #output>
+            #where_clause
+        {
+            use ::spaad::export::xtra::prelude::*;
+
+            let __spaad_caller = ::std::panic::Location::caller();
+            let f = self.addr.send(#msg);
+            async move {
+                #response
+                response
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Small stand-ins for third party macros, used by spaad's examples to check that spaad works
+//! alongside them.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Fields, FnArg, ImplItem, ItemImpl, ItemStruct};
+
+/// Derives an associated function `injected_fields` listing the names of the struct's fields, like
+/// a dependency injection framework might inspect them.
+#[proc_macro_derive(Injectable)]
+pub fn derive_injectable(input: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(input as ItemStruct);
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let fields: Vec<String> = match &item.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| field.ident.as_ref().unwrap().to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    TokenStream::from(quote! {
+        impl#impl_generics #ident#ty_generics #where_clause {
+            pub fn injected_fields() -> &'static [&'static str] {
+                &[#(#fields),*]
+            }
+        }
+    })
+}
+
+/// Records the name of every method with a receiver in a `trace: Vec<&'static str>` field of the
+/// struct when it is called, like a logging macro might log them.
+#[proc_macro_attribute]
+pub fn trace(_args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut item = syn::parse_macro_input!(input as ItemImpl);
+    for impl_item in &mut item.items {
+        if let ImplItem::Method(method) = impl_item {
+            if let Some(FnArg::Receiver(_)) = method.sig.inputs.first() {
+                let name = method.sig.ident.to_string();
+                method
+                    .block
+                    .stmts
+                    .insert(0, syn::parse_quote!(self.trace.push(#name);));
+            }
+        }
+    }
+
+    TokenStream::from(quote!(#item))
+}
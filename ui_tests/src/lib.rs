@@ -0,0 +1,3 @@
+//! Tests of the errors reported by spaad's macros. They are run with
+//! `cargo test --manifest-path ui_tests/Cargo.toml`, and `TRYBUILD=overwrite` updates the expected
+//! output after a change to a message.
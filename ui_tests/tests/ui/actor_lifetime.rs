@@ -0,0 +1,6 @@
+#[spaad::entangled]
+struct Printer<'a> {
+    prefix: &'a str,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Counter<T> {
+    count: T,
+}
+
+#[spaad::entangled]
+impl<T> Actor for Counter<T> {}
+
+pub type Count = Counter<u32>;
+
+// The macro only sees the alias, and has to be told which actor it stands for
+#[spaad::entangled]
+impl Count {
+    #[spaad::handler]
+    pub fn get(&self) -> u32 {
+        self.count
+    }
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+struct Printer;
+
+#[spaad::entangled]
+impl Actor for Printer {}
+
+// `all()` always holds and `any()` never does, standing in for a feature which is enabled and one
+// which isn't
+#[spaad::entangled(fire_and_forget)]
+impl Printer {
+    #[spaad::handler]
+    #[cfg_attr(all(), spaad::handler(send))]
+    fn print(&mut self) {}
+
+    #[spaad::handler]
+    #[cfg_attr(any(), spaad::handler(send))]
+    fn flush(&mut self) {}
+}
+
+async fn use_printer(printer: Printer) {
+    // Waited on, so this is a future rather than `()`
+    let () = printer.print();
+    // Fire and forget, so there is nothing to wait on
+    printer.flush().await;
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+#![deny(deprecated)]
+
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Printer;
+
+#[spaad::entangled]
+impl Actor for Printer {}
+
+#[spaad::entangled]
+impl Printer {
+    #[spaad::handler]
+    #[deprecated(since = "0.2.0", note = "use `print_line` instead")]
+    pub fn print(&self, line: String) {
+        println!("{}", line);
+    }
+}
+
+pub async fn print(printer: Printer) {
+    printer.print("hello".to_string()).await;
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+struct Counter {
+    count: u64,
+}
+
+#[spaad::entangled]
+impl Actor for Counter {}
+
+#[spaad::entangled]
+impl Counter {
+    #[spaad::handler]
+    fn get(&self) -> u64 {
+        self.count
+    }
+}
+
+#[spaad::entangled]
+impl Counter {
+    #[spaad::handler]
+    fn get(&self) -> u64 {
+        self.count * 2
+    }
+}
+
+fn main() {}
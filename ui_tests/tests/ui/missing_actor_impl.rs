@@ -0,0 +1,10 @@
+#[spaad::entangled]
+struct Printer;
+
+#[spaad::entangled]
+impl Printer {
+    #[spaad::handler]
+    fn print(&mut self) {}
+}
+
+fn main() {}
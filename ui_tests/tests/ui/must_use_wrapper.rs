@@ -0,0 +1,23 @@
+#![deny(unused_must_use)]
+
+use xtra::prelude::*;
+
+#[spaad::entangled(stop_on_last_drop, must_use)]
+pub struct Printer;
+
+#[spaad::entangled]
+impl Actor for Printer {}
+
+#[spaad::entangled]
+impl Printer {
+    #[spaad::spawn]
+    pub fn new() -> Self {
+        Printer
+    }
+}
+
+pub fn spawn(spawner: &mut impl xtra::spawn::Spawner) {
+    Printer::new(spawner);
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+struct Printer;
+
+#[spaad::entangled]
+impl Actor for Printer {}
+
+#[spaad::entangled]
+impl Printer {
+    #[spaad::handler]
+    fn print(self: std::pin::Pin<&mut Self>) {}
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Directory {
+    address: String,
+}
+
+#[spaad::entangled]
+impl Actor for Directory {}
+
+#[spaad::entangled]
+impl Directory {
+    // Every wrapper already has an `address` method, returning the actor's address
+    #[spaad::handler]
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+}
+
+fn main() {}
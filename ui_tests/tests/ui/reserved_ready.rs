@@ -0,0 +1,20 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Loader {
+    loaded: bool,
+}
+
+#[spaad::entangled]
+impl Actor for Loader {}
+
+#[spaad::entangled]
+impl Loader {
+    // Every wrapper already has a `ready` method, resolving once the actor has started
+    #[spaad::handler]
+    pub fn ready(&self) -> bool {
+        self.loaded
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+struct Counter {
+    count: u64,
+}
+
+#[spaad::entangled]
+impl Actor for Counter {}
+
+#[spaad::entangled]
+impl Counter {
+    #[spaad::handler]
+    fn merge(&mut self, other: Self) {
+        self.count += other.count;
+    }
+}
+
+fn main() {}
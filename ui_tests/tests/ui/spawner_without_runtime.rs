@@ -0,0 +1,17 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+struct Printer;
+
+#[spaad::entangled]
+impl Actor for Printer {}
+
+#[spaad::entangled]
+impl Printer {
+    #[spaad::spawn(spawner = "tokio")]
+    fn new() -> Self {
+        Printer
+    }
+}
+
+fn main() {}
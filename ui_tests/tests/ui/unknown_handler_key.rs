@@ -0,0 +1,18 @@
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Printer {}
+
+#[spaad::entangled]
+impl Actor for Printer {}
+
+#[spaad::entangled]
+impl Printer {
+    // A misspelt flag is an error, rather than being ignored
+    #[spaad::handler(brodcast)]
+    pub fn print(&mut self, to_print: String) {
+        println!("{}", to_print);
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use std::rc::Rc;
+use xtra::prelude::*;
+
+#[spaad::entangled]
+pub struct Printer {}
+
+#[spaad::entangled]
+impl Actor for Printer {}
+
+#[spaad::entangled]
+impl Printer {
+    // An `Rc` can't be moved into the message, which is sent to the actor's thread
+    #[spaad::handler]
+    pub fn print(&mut self, to_print: Rc<String>) {
+        println!("{}", to_print);
+    }
+}
+
+fn main() {}